@@ -4,10 +4,247 @@
 
 use std::collections::{HashMap, HashSet};
 
-use pyly::{python as py, Exposed, Python as Py};
+use pyly::{python as py, typescript as ts, Exposed, Python as Py, TypeScript as Ts};
 
+#[pyly::expose]
 struct A(usize, usize);
 
+#[pyly::expose]
+pub struct Pair {
+    left: A,
+    right: A,
+    label: String,
+    seen: HashSet<u16>,
+    history: Vec<A>,
+    nickname: Option<String>,
+}
+
+#[pyly::expose]
+impl Pair {
+    fn new(left: A, right: A, label: String) -> Self {
+        Self {
+            left,
+            right,
+            label,
+            seen: HashSet::new(),
+            history: Vec::new(),
+            nickname: None,
+        }
+    }
+
+    pub fn describe(&self) -> &str {
+        self.nickname.as_deref().unwrap_or(&self.label)
+    }
+
+    pub fn rename(&mut self, nickname: Option<String>) {
+        self.nickname = nickname;
+    }
+}
+
+/// A point of the compass.
+#[pyly::expose]
+pub enum Direction {
+    /// Up, on a map.
+    North,
+    East,
+    South,
+    West,
+}
+
+#[pyly::expose]
+impl Direction {
+    pub fn opposite(&self) -> Direction {
+        match self {
+            Direction::North => Direction::South,
+            Direction::East => Direction::West,
+            Direction::South => Direction::North,
+            Direction::West => Direction::East,
+        }
+    }
+}
+
+#[pyly::expose]
+#[repr(i8)]
+pub enum Level {
+    Low = -1,
+    High = 10,
+}
+
+#[pyly::expose]
+pub enum Shape {
+    /// Every point [`radius`](Shape::Circle::radius) away from the origin.
+    Circle {
+        radius: f64,
+    },
+    Line(Direction, f64),
+    Point,
+}
+
+#[pyly::expose]
+impl Shape {
+    pub fn area(&self) -> f64 {
+        match self {
+            Shape::Circle { radius } => std::f64::consts::PI * radius * radius,
+            Shape::Line(..) | Shape::Point => 0.0,
+        }
+    }
+}
+
+#[pyly::expose]
+pub fn distance(shape: &Shape, (x, y): (f64, f64)) -> f64 {
+    match shape {
+        Shape::Circle { radius } => (x.hypot(y) - radius).abs(),
+        Shape::Line(..) | Shape::Point => x.hypot(y),
+    }
+}
+
+/// Where a [`Shape`] is: see [`center`].
+#[pyly::expose(name = "Point2D", module = "geometry.shapes")]
+pub struct Point {
+    /// How far right of the origin it is.
+    #[pyly(rename = "x")]
+    left: f64,
+    #[pyly(rename = "y")]
+    up: f64,
+    #[pyly(skip)]
+    label: Option<String>,
+}
+
+#[pyly::expose(name = "centre")]
+pub fn center(shape: &Shape) -> Point {
+    Point {
+        left: 0.0,
+        up: 0.0,
+        label: None,
+    }
+}
+
+/// A page of items.
+///
+/// Get one with [`Page::get`], or make a page with [`page()`].
+#[pyly::expose]
+pub struct Page<T> {
+    items: Vec<T>,
+    number: usize,
+}
+
+#[pyly::expose]
+impl<U> Page<U> {
+    pub fn last(mut self) -> Option<U> {
+        self.items.pop()
+    }
+
+    /// The item at `index`, unless that's past the end (an [`OutOfBounds`]).
+    pub fn get(&self, index: usize) -> Result<&U, OutOfBounds> {
+        self.items.get(index).ok_or(OutOfBounds(index))
+    }
+
+    pub fn zip<V>(self, other: Page<V>) -> Page<(U, V)> {
+        Page {
+            items: self.items.into_iter().zip(other.items).collect(),
+            number: self.number,
+        }
+    }
+}
+
+#[pyly::expose]
+pub fn page(items: Vec<String>, number: usize) -> Page<String> {
+    Page { items, number }
+}
+
+/// Visits each item of a [`Page`], until told to stop.
+#[pyly::expose]
+pub trait Visitor<T> {
+    /// Whether to carry on.
+    fn visit(&mut self, item: &T) -> bool;
+
+    fn fresh() -> Self
+    where
+        Self: Sized;
+}
+
+#[pyly::expose]
+pub trait Limited {
+    const LIMIT: usize;
+}
+
+#[pyly::expose]
+pub fn walk(page: &Page<String>, visitor: &mut dyn Visitor<String>) -> usize {
+    page.items
+        .iter()
+        .take_while(|item| visitor.visit(item))
+        .count()
+}
+
+/// The most items a [`Page`] holds.
+#[pyly::expose]
+pub const PAGE_SIZE: usize = 50;
+
+#[pyly::expose(name = "GREETING")]
+pub const HELLO: &str = "Hello, \"world\"!";
+
+#[pyly::expose]
+pub const STRICT: bool = true;
+
+#[pyly::expose]
+pub const OFFSET: i16 = -3;
+
+#[pyly::expose]
+pub const ORIGIN: (f64, f64) = (0.0, 0.0);
+
+pub mod colors {
+    use crate::Shape;
+
+    /// A primary colour.
+    #[pyly::expose]
+    pub enum Color {
+        Red,
+        Green,
+        Blue,
+    }
+
+    /// The colour to fill a [`Shape`] with.
+    #[pyly::expose]
+    pub fn fill(shape: &Shape) -> Color {
+        Color::Red
+    }
+}
+
+pub use colors::{fill as paint, Color};
+
+pub struct OutOfBounds(usize);
+
+impl Exposed<Py> for OutOfBounds {
+    const AS: <Py as pyly::Language>::Type = py::Type::Custom {
+        module: "builtins",
+        name: "IndexError",
+    };
+}
+
+impl Exposed<Ts> for OutOfBounds {
+    const AS: <Ts as pyly::Language>::Type = ts::Type::Interface {
+        module: "",
+        name: "RangeError",
+    };
+}
+
+/// Every [page](Page) of a book.
+pub struct Chapters(Vec<Page<String>>);
+
+impl Exposed<Py> for Chapters {
+    const AS: <Py as pyly::Language>::Type = <Vec<Page<String>> as Exposed<Py>>::AS;
+}
+
+pub struct Stack<T>(Vec<T>);
+
+impl<T: Exposed<Py>> Exposed<Py> for Stack<T> {
+    const AS: <Py as pyly::Language>::Type = <Vec<T> as Exposed<Py>>::AS;
+}
+
+impl<T: Exposed<Ts>> Exposed<Ts> for Stack<T> {
+    const AS: <Ts as pyly::Language>::Type = <Vec<T> as Exposed<Ts>>::AS;
+}
+
 pub struct Svelte {
     a1: A,
     a2: A,
@@ -21,7 +258,18 @@ impl Exposed<Py> for Svelte {
         bool,
         &'static str,
         (Vec<u8>, HashSet<u16>, HashMap<String, usize>),
-    )>::AS;
+    ) as Exposed<Py>>::AS;
+}
+
+impl Exposed<Ts> for Svelte {
+    const AS: <Ts as pyly::Language>::Type = <(
+        (),
+        u8,
+        f32,
+        bool,
+        &'static str,
+        (Vec<u8>, HashSet<u16>, HashMap<String, usize>),
+    ) as Exposed<Ts>>::AS;
 }
 
 //ad adsba sdsaadsasddas
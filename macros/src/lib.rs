@@ -2,7 +2,9 @@
 //! PyLy Helper Macros
 //!
 
-use quote::ToTokens;
+use proc_macro_crate::FoundCrate;
+use quote::{ToTokens, quote};
+use syn::ext::IdentExt;
 
 fn s(ident: &str) -> syn::PathSegment {
     syn::PathSegment {
@@ -11,34 +13,323 @@ fn s(ident: &str) -> syn::PathSegment {
     }
 }
 
+/// `#[__pyly::{attr}]`, or `#[__pyly::{attr} = value]`,
+/// for the plugin to read back.
+fn tool(attr: &str, value: Option<syn::LitStr>) -> syn::Attribute {
+    let path = syn::Path {
+        leading_colon: None,
+        segments: syn::punctuated::Punctuated::from_iter([s("__pyly"), s(attr)]),
+    };
+
+    syn::Attribute {
+        pound_token: Default::default(),
+        style: syn::AttrStyle::Outer,
+        bracket_token: Default::default(),
+        meta: match value {
+            None => syn::Meta::Path(path),
+            Some(value) => syn::Meta::NameValue(syn::MetaNameValue {
+                path,
+                eq_token: Default::default(),
+                value: syn::Expr::Lit(syn::ExprLit {
+                    attrs: vec![],
+                    lit: syn::Lit::Str(value),
+                }),
+            }),
+        },
+    }
+}
+
+/// Set `slot` to the option's (string) value, unless it's been given already.
+fn option(meta: &syn::meta::ParseNestedMeta, slot: &mut Option<syn::LitStr>) -> syn::Result<()> {
+    if slot.is_some() {
+        return Err(meta.error("duplicate option"));
+    }
+
+    *slot = Some(meta.value()?.parse()?);
+    Ok(())
+}
+
+/// The options of `#[pyly::expose(...)]`.
+#[derive(Default)]
+struct ExposeOptions {
+    /// `name = "..."`: what it's called in the stubs.
+    name: Option<syn::LitStr>,
+    /// `module = "..."`: the (dotted) module it's declared in.
+    module: Option<syn::LitStr>,
+    /// `crate = "..."`: the path to `pyly_lib`, for the impls generated for it.
+    krate: Option<syn::LitStr>,
+}
+
+impl ExposeOptions {
+    fn parse(&mut self, meta: syn::meta::ParseNestedMeta) -> syn::Result<()> {
+        if meta.path.is_ident("name") {
+            option(&meta, &mut self.name)
+        } else if meta.path.is_ident("module") {
+            option(&meta, &mut self.module)
+        } else if meta.path.is_ident("crate") {
+            option(&meta, &mut self.krate)
+        } else {
+            let key = meta.path.to_token_stream().to_string().replace(' ', "");
+            Err(meta.error(format!(
+                "unknown option `{key}`, expected `name`, `module` or `crate`"
+            )))
+        }
+    }
+
+    /// The path to `pyly_lib`: the `crate` option's, or else whatever the crate
+    /// using the macro calls it (by default, `::pyly_lib`).
+    fn pyly_lib(&self) -> syn::Result<syn::Path> {
+        if let Some(krate) = &self.krate {
+            return krate.parse();
+        }
+
+        match proc_macro_crate::crate_name("pyly-lib") {
+            Ok(FoundCrate::Name(name)) => {
+                let name = syn::Ident::new(&name, proc_macro::Span::call_site().into());
+                Ok(syn::parse_quote!(::#name))
+            }
+            // Used by `pyly_lib` itself, or without a manifest to go by.
+            Ok(FoundCrate::Itself) | Err(_) => Ok(syn::parse_quote!(::pyly_lib)),
+        }
+    }
+
+    fn into_attrs(self) -> impl Iterator<Item = syn::Attribute> {
+        let name = self.name.map(|name| tool("__name", Some(name)));
+        let module = self.module.map(|module| tool("__module", Some(module)));
+
+        name.into_iter().chain(module)
+    }
+}
+
+/// Add `error` to those already found.
+fn push_error(errors: &mut Option<syn::Error>, error: syn::Error) {
+    match errors {
+        Some(errors) => errors.combine(error),
+        None => *errors = Some(error),
+    }
+}
+
+/// Swap each field's `#[pyly(skip)]` and `#[pyly(rename = "...")]`
+/// for the plugin's own attributes.
+fn field_options(fields: &mut syn::Fields, errors: &mut Option<syn::Error>) {
+    for field in fields.iter_mut() {
+        let (options, attrs) = std::mem::take(&mut field.attrs)
+            .into_iter()
+            .partition::<Vec<_>, _>(|attr| attr.path().is_ident("pyly"));
+        field.attrs = attrs;
+
+        for attr in options {
+            let mut rename = None;
+            let result = attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("skip") {
+                    field.attrs.push(tool("__skip", None));
+                    Ok(())
+                } else if meta.path.is_ident("rename") {
+                    option(&meta, &mut rename)
+                } else {
+                    let key = meta.path.to_token_stream().to_string().replace(' ', "");
+                    Err(meta.error(format!(
+                        "unknown option `{key}`, expected `skip` or `rename`"
+                    )))
+                }
+            });
+
+            field
+                .attrs
+                .extend(rename.map(|rename| tool("__rename", Some(rename))));
+
+            if let Err(error) = result {
+                push_error(errors, error);
+            }
+        }
+    }
+}
+
+/// ```ignore
+/// impl<T> pyly_lib::Exposed<pyly_lib::Python> for Type<T>
+/// where
+///     T: pyly_lib::Exposed<pyly_lib::Python>,
+/// {
+///     const AS: pyly_lib::python::Type<'static> = pyly_lib::python::Type::Class {
+///         module: module_path!(),
+///         name: "Type",
+///         args: &[<T as pyly_lib::Exposed<pyly_lib::Python>>::AS],
+///     };
+/// }
+/// ```
+///
+/// And the same for `pyly_lib::TypeScript`, with a `pyly_lib::typescript::Type::Class`.
 ///
-/// Expose a Rust type to Python.
+/// So that other types can refer to the class (or interface) declared for it.
+fn exposed_impl(
+    ident: &syn::Ident,
+    generics: &syn::Generics,
+    options: &ExposeOptions,
+    pyly: &syn::Path,
+) -> proc_macro2::TokenStream {
+    let name = match &options.name {
+        Some(name) => name.clone(),
+        None => syn::LitStr::new(&ident.unraw().to_string(), ident.span()),
+    };
+    let module = match &options.module {
+        Some(module) => module.to_token_stream(),
+        None => quote!(::core::module_path!()),
+    };
+
+    let params = generics
+        .type_params()
+        .map(|param| param.ident.clone())
+        .collect::<Vec<_>>();
+
+    let exposed_to = |language: proc_macro2::TokenStream, ty: proc_macro2::TokenStream| {
+        let mut generics = generics.clone();
+        let where_clause = generics.make_where_clause();
+        for param in &params {
+            where_clause
+                .predicates
+                .push(syn::parse_quote!(#param: #pyly::Exposed<#language>));
+        }
+
+        let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+        quote! {
+            impl #impl_generics #pyly::Exposed<#language> for #ident #ty_generics #where_clause {
+                const AS: #ty<'static> = #ty::Class {
+                    module: #module,
+                    name: #name,
+                    args: &[#(<#params as #pyly::Exposed<#language>>::AS),*],
+                };
+            }
+        }
+    };
+
+    let mut tokens = exposed_to(quote!(#pyly::Python), quote!(#pyly::python::Type));
+    tokens.extend(exposed_to(
+        quote!(#pyly::TypeScript),
+        quote!(#pyly::typescript::Type),
+    ));
+    tokens
+}
+
+///
+/// Expose a Rust type, trait (as a protocol), function, `const` (as a `Final` literal),
+/// or `impl` block's methods to Python.
+///
+/// Types, functions, traits and consts can be given another `name` in the stubs,
+/// or be declared in another (dotted) `module`.
+/// Fields can be skipped, or renamed, with `#[pyly(...)]`.
+///
+/// Structs and enums also get `Exposed<Python>` and `Exposed<TypeScript>` impls,
+/// referring to their class (or interface), so need nothing more to be used
+/// in other exposed types.
+/// These find `pyly_lib` by whatever the crate's manifest calls it,
+/// unless given its path as `crate = "..."`.
 ///
 /// ### Examples
 /// ```ignore
 /// #[pyly::expose]
 /// pub struct MyCoolType;
+///
+/// #[pyly::expose]
+/// pub enum MyCoolEnum {
+///     A,
+///     B(MyCoolType),
+/// }
+///
+/// #[pyly::expose]
+/// impl MyCoolType {
+///     pub fn new() -> Self {
+///         MyCoolType
+///     }
+/// }
+///
+/// #[pyly::expose]
+/// pub fn cool(ty: &MyCoolType) -> bool {
+///     true
+/// }
+///
+/// #[pyly::expose]
+/// pub const COOLEST: u8 = 10;
+///
+/// #[pyly::expose(name = "Point2D", module = "geometry.shapes")]
+/// pub struct Point {
+///     #[pyly(rename = "x")]
+///     left: f64,
+///     #[pyly(rename = "y")]
+///     up: f64,
+///     #[pyly(skip)]
+///     cache: Vec<u8>,
+/// }
+///
+/// #[pyly::expose(crate = "my_reexports::pyly")]
+/// pub struct Elsewhere;
 /// ```
 ///
 #[proc_macro_attribute]
 pub fn expose(
-    _attr: proc_macro::TokenStream,
+    attr: proc_macro::TokenStream,
     item: proc_macro::TokenStream,
 ) -> proc_macro::TokenStream {
+    let mut errors = None;
+
+    let mut options = ExposeOptions::default();
+    let parser = syn::meta::parser(|meta| options.parse(meta));
+    if let Err(error) = syn::parse::Parser::parse(parser, attr) {
+        push_error(&mut errors, error);
+    }
+
     let mut item: syn::Item = syn::parse_macro_input!(item);
+
+    match &mut item {
+        syn::Item::Struct(syn::ItemStruct { fields, .. }) => field_options(fields, &mut errors),
+        syn::Item::Enum(syn::ItemEnum { variants, .. }) => {
+            for variant in variants {
+                field_options(&mut variant.fields, &mut errors);
+            }
+        }
+        syn::Item::Impl(_) => {
+            if let Some(option) = options.name.as_ref().or(options.module.as_ref()) {
+                let error = syn::Error::new_spanned(
+                    option,
+                    "`impl` blocks can't be renamed or moved, only their type",
+                );
+                push_error(&mut errors, error);
+            }
+        }
+        _ => (),
+    }
+
+    let exposed = match &item {
+        syn::Item::Struct(syn::ItemStruct {
+            ident, generics, ..
+        })
+        | syn::Item::Enum(syn::ItemEnum {
+            ident, generics, ..
+        }) => match options.pyly_lib() {
+            Ok(pyly) => Some(exposed_impl(ident, generics, &options, &pyly)),
+            Err(error) => {
+                push_error(&mut errors, error);
+                None
+            }
+        },
+        _ => None,
+    };
+
     if let syn::Item::Struct(syn::ItemStruct { attrs, .. })
-    | syn::Item::Trait(syn::ItemTrait { attrs, .. }) = &mut item
+    | syn::Item::Enum(syn::ItemEnum { attrs, .. })
+    | syn::Item::Trait(syn::ItemTrait { attrs, .. })
+    | syn::Item::Fn(syn::ItemFn { attrs, .. })
+    | syn::Item::Const(syn::ItemConst { attrs, .. })
+    | syn::Item::Impl(syn::ItemImpl { attrs, .. }) = &mut item
     {
-        attrs.push(syn::Attribute {
-            pound_token: Default::default(),
-            style: syn::AttrStyle::Outer,
-            bracket_token: Default::default(),
-            meta: syn::Meta::Path(syn::Path {
-                leading_colon: None,
-                segments: syn::punctuated::Punctuated::from_iter([s("__pyly"), s("__expose")]),
-            }),
-        });
+        attrs.push(tool("__expose", None));
+        attrs.extend(options.into_attrs());
     };
 
-    item.into_token_stream().into()
+    let mut tokens = item.into_token_stream();
+    tokens.extend(exposed);
+    if let Some(errors) = errors {
+        tokens.extend(errors.into_compile_error());
+    }
+
+    tokens.into()
 }
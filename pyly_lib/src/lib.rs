@@ -1,5 +1,12 @@
-#![allow(internal_features)]
-#![feature(core_intrinsics, const_eval_select, const_copy_from_slice, const_heap)]
+#![allow(internal_features, incomplete_features)]
+#![feature(
+    core_intrinsics,
+    const_eval_select,
+    const_copy_from_slice,
+    const_heap,
+    adt_const_params,
+    unsized_const_params
+)]
 
 #[cfg(feature = "macros")]
 pub use pyly_macros::expose;
@@ -16,6 +23,11 @@ pub trait Language: __private::_Private {
 
 pub trait Exposed<L: Language> {
     const AS: L::Type;
+
+    /// What a `Vec` or slice of these is exposed as, if not a list of [Exposed::AS]
+    /// (e.g. `bytes`, for `u8`).
+    #[doc(hidden)]
+    const AS_LIST: Option<L::Type> = None;
 }
 
 macro_rules! impl_lang_for {
@@ -35,11 +47,76 @@ macro_rules! impl_lang_for {
     };
 }
 
+/// The members of a union of `items`, with nested unions (the members `inner` gives)
+/// flattened, and without any repeats.
+fn union_members<'a, T: PartialEq>(
+    items: &'a [T],
+    inner: fn(&'a T) -> Option<&'a [T]>,
+) -> Vec<&'a T> {
+    fn flatten<'a, T: PartialEq>(
+        items: &'a [T],
+        inner: fn(&'a T) -> Option<&'a [T]>,
+        members: &mut Vec<&'a T>,
+    ) {
+        for item in items {
+            match inner(item) {
+                Some(nested) => flatten(nested, inner, members),
+                None if members.contains(&item) => {}
+                None => members.push(item),
+            }
+        }
+    }
+
+    let mut members = vec![];
+    flatten(items, inner, &mut members);
+    members
+}
+
+/// Stands in for the generic parameter `NAME` of an exposed type,
+/// so that types using it can still be evaluated:
+/// `Vec<TypeParam<"T">>` is exposed to Python as `list[T]`.
+pub struct TypeParam<const NAME: &'static str>;
+
+impl<const NAME: &'static str> Exposed<Python> for TypeParam<NAME> {
+    const AS: python::Type<'static> = python::Type::TypeVar(NAME);
+}
+
+impl<const NAME: &'static str> Exposed<TypeScript> for TypeParam<NAME> {
+    const AS: typescript::Type<'static> = typescript::Type::Parameter(NAME);
+}
+
+/// Exposed to Python as `bytes`, whatever it wraps
+/// (say, a `[u8; 4]`, which is otherwise a `list[int]`).
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Bytes<T = Vec<u8>>(pub T);
+
+/// Exposed to Python as (a mutable) `bytearray`, whatever it wraps.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ByteArray<T = Vec<u8>>(pub T);
+
+/// Exposed to Python as `complex`, whatever it wraps
+/// (say, a `(re, im)` pair).
+///
+/// With the `num-complex` feature, `num_complex::Complex` is too.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Complex<T = (f64, f64)>(pub T);
+
+/// A tuple of any length, all of `T`: exposed to Python as `tuple[T, ...]`.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct TupleOf<T>(pub Vec<T>);
+
 pub struct Python;
 impl __private::_Private for Python {}
 impl Language for Python {
     type Type = python::Type<'static>;
 }
+
+pub struct TypeScript;
+impl __private::_Private for TypeScript {}
+impl Language for TypeScript {
+    type Type = typescript::Type<'static>;
+}
+
 #[allow(non_camel_case_types)]
 pub mod python {
     macro_rules! impl_tuple {
@@ -51,6 +128,25 @@ pub mod python {
         }
     }
 
+    macro_rules! impl_callable {
+        ($($ai: ident),*) => {
+            impl_callable!(@impl [$($ai),*] fn($($ai),*) -> R);
+            // Boxed (or borrowed) via the impls for pointers.
+            impl_callable!(@impl [$($ai),*] dyn Fn($($ai),*) -> R);
+            impl_callable!(@impl [$($ai),*] dyn FnMut($($ai),*) -> R);
+            impl_callable!(@impl [$($ai),*] dyn FnOnce($($ai),*) -> R);
+        };
+        (@impl [$($ai: ident),*] $target: ty) => {
+            #[doc = "This trait is implemented for callables taking up to six arguments."]
+            impl<$($ai : crate::Exposed<Py>,)* R: crate::Exposed<Py>> crate::Exposed<Py> for $target {
+                const AS: <Py as $crate::Language>::Type = Typing(Typing::Callable {
+                    params: &[$($ai ::AS),*],
+                    returns: &R::AS,
+                });
+            }
+        };
+    }
+
     #[repr(C, u8)]
     #[derive(Debug, PartialEq, Eq)]
     pub enum InBuilt<'a> {
@@ -69,29 +165,33 @@ pub mod python {
         Dict(&'a [Type<'a>; 2]),
     }
 
-    /// THIS LEAKS MEMORY!
-    const fn generic_format(start: &str, items: &[Type<'_>]) -> &'static str {
-        const unsafe fn slice_mut<T>(slice: &mut [T], start: usize, end: usize) -> &mut [T] {
-            if start > slice.len() || end > slice.len() {
-                panic!("Out of bounds!");
-            }
-
-            let ptr = slice.as_mut_ptr();
-            let ptr = unsafe { ptr.add(start) };
-            unsafe { core::slice::from_raw_parts_mut(ptr, end - start) }
+    const unsafe fn slice_mut<T>(slice: &mut [T], start: usize, end: usize) -> &mut [T] {
+        if start > slice.len() || end > slice.len() {
+            panic!("Out of bounds!");
         }
 
-        const unsafe fn copy_into_slice_at<T: Copy>(
-            dest: &mut [T],
-            start: usize,
-            src: &[T],
-        ) -> usize {
-            let end = start + src.len();
-            let subslice = unsafe { slice_mut(dest, start, end) };
-            subslice.copy_from_slice(src);
-            end
-        }
+        let ptr = slice.as_mut_ptr();
+        let ptr = unsafe { ptr.add(start) };
+        unsafe { core::slice::from_raw_parts_mut(ptr, end - start) }
+    }
 
+    const unsafe fn copy_into_slice_at<T: Copy>(dest: &mut [T], start: usize, src: &[T]) -> usize {
+        let end = start + src.len();
+        let subslice = unsafe { slice_mut(dest, start, end) };
+        subslice.copy_from_slice(src);
+        end
+    }
+
+    /// What [Type::as_str] (and the like) do at runtime, where there's nowhere
+    /// to keep the names they build (short of leaking them).
+    fn only_at_compile_time() -> ! {
+        panic!("`as_str` only works at compile-time: use `Display` (or `write_to`) at runtime")
+    }
+
+    /// `start[items, ...]`
+    ///
+    /// Only at compile-time: at runtime, use [fmt::Display] or `write_to` instead.
+    const fn generic_format(start: &str, items: &[Type<'_>]) -> &'static str {
         const fn compiletime(start: &str, items: &[Type<'_>]) -> &'static str {
             let mut sum_type_lengths = 0;
             {
@@ -144,28 +244,343 @@ pub mod python {
             unsafe { str::from_utf8_unchecked_mut(output) }
         }
 
-        fn runtime(start: &str, items: &[Type<'_>]) -> &'static str {
-            let mut s = String::new();
-            write!(s, "{start}[").unwrap();
+        fn runtime(_start: &str, _items: &[Type<'_>]) -> &'static str {
+            only_at_compile_time()
+        }
+
+        std::intrinsics::const_eval_select((start, items), compiletime, runtime)
+    }
+
+    /// How many members a union has, once any nested unions are flattened.
+    const fn union_len(items: &[Type<'_>]) -> usize {
+        let mut len = 0;
+        let mut i = 0;
+        loop {
+            if i >= items.len() {
+                break;
+            }
+
+            len += match &items[i] {
+                Type::Union(inner) => union_len(inner),
+                _ => 1,
+            };
+            i += 1;
+        }
+
+        len
+    }
+
+    /// The `n`th member of a union, once any nested unions are flattened.
+    const fn union_nth<'a>(items: &'a [Type<'a>], mut n: usize) -> &'a Type<'a> {
+        let mut i = 0;
+        loop {
+            if i >= items.len() {
+                panic!("Out of bounds!");
+            }
 
-            for (i, item) in items.iter().enumerate() {
-                write!(s, "{}", item.as_str()).unwrap();
+            match &items[i] {
+                Type::Union(inner) => {
+                    let len = union_len(inner);
+                    if n < len {
+                        return union_nth(inner, n);
+                    }
 
-                if i < (items.len() - 1) {
-                    write!(s, ", ").unwrap();
+                    n -= len;
                 }
+                item if n == 0 => return item,
+                _ => n -= 1,
             }
 
-            write!(s, "]").unwrap();
+            i += 1;
+        }
+    }
 
-            // BUG: FIX THIS INTENTIONAL MEMORY LEAK AT RUNTIME!
-            Box::leak(s.into_boxed_str())
+    const fn str_eq(a: &str, b: &str) -> bool {
+        let (a, b) = (a.as_bytes(), b.as_bytes());
+        if a.len() != b.len() {
+            return false;
         }
 
-        std::intrinsics::const_eval_select((start, items), compiletime, runtime)
+        let mut i = 0;
+        loop {
+            if i >= a.len() {
+                return true;
+            }
+
+            if a[i] != b[i] {
+                return false;
+            }
+
+            i += 1;
+        }
+    }
+
+    /// Does the `n`th member of a union render the same as an earlier one?
+    const fn union_repeats(items: &[Type<'_>], n: usize) -> bool {
+        let member = union_nth(items, n).as_str();
+
+        let mut i = 0;
+        loop {
+            if i >= n {
+                return false;
+            }
+
+            if str_eq(union_nth(items, i).as_str(), member) {
+                return true;
+            }
+
+            i += 1;
+        }
+    }
+
+    /// `A | B | ...`, flattened and without repeats.
+    ///
+    /// Just like [generic_format], this only works at compile-time.
+    const fn union_format(items: &[Type<'_>]) -> &'static str {
+        const fn compiletime(items: &[Type<'_>]) -> &'static str {
+            let members = union_len(items);
+
+            let mut length = 0;
+            let mut unique = 0;
+            {
+                let mut i = 0;
+                loop {
+                    if i >= members {
+                        break;
+                    }
+
+                    if !union_repeats(items, i) {
+                        length += union_nth(items, i).as_str().len();
+                        unique += 1;
+                    }
+                    i += 1;
+                }
+            }
+
+            if unique == 0 {
+                return NEVER;
+            }
+
+            length += " | ".len() * (unique - 1);
+
+            let output = unsafe {
+                core::slice::from_raw_parts_mut(std::intrinsics::const_allocate(length, 1), length)
+            };
+
+            let mut output_i = 0;
+            let mut i = 0;
+            loop {
+                if i >= members {
+                    break;
+                }
+
+                if !union_repeats(items, i) {
+                    if output_i > 0 {
+                        output_i = unsafe { copy_into_slice_at(output, output_i, b" | ") };
+                    }
+
+                    let member = union_nth(items, i).as_str();
+                    output_i = unsafe { copy_into_slice_at(output, output_i, member.as_bytes()) };
+                }
+                i += 1;
+            }
+
+            assert!(output.len() == output_i);
+            unsafe { str::from_utf8_unchecked_mut(output) }
+        }
+
+        fn runtime(_items: &[Type<'_>]) -> &'static str {
+            only_at_compile_time()
+        }
+
+        std::intrinsics::const_eval_select((items,), compiletime, runtime)
+    }
+
+    /// `typing.Literal[values, ...]`
+    ///
+    /// Just like [generic_format], this only works at compile-time.
+    const fn literal_format(values: &[Literal<'_>]) -> &'static str {
+        const START: &str = "typing.Literal[";
+
+        const fn compiletime(values: &[Literal<'_>]) -> &'static str {
+            let mut length = START.len() + ", ".len() * values.len().saturating_sub(1) + "]".len();
+            let mut i = 0;
+            while i < values.len() {
+                length += values[i].len();
+                i += 1;
+            }
+
+            let output = unsafe {
+                core::slice::from_raw_parts_mut(std::intrinsics::const_allocate(length, 1), length)
+            };
+
+            let mut output_i = unsafe { copy_into_slice_at(output, 0, START.as_bytes()) };
+            let mut i = 0;
+            while i < values.len() {
+                if i > 0 {
+                    output_i = unsafe { copy_into_slice_at(output, output_i, b", ") };
+                }
+
+                output_i = unsafe { values[i].copy_into_slice_at(output, output_i) };
+                i += 1;
+            }
+
+            output_i = unsafe { copy_into_slice_at(output, output_i, b"]") };
+
+            assert!(output.len() == output_i);
+            unsafe { str::from_utf8_unchecked_mut(output) }
+        }
+
+        fn runtime(_values: &[Literal<'_>]) -> &'static str {
+            only_at_compile_time()
+        }
+
+        std::intrinsics::const_eval_select((values,), compiletime, runtime)
+    }
+
+    /// All of `parts`, one after the other.
+    ///
+    /// Just like [generic_format], this only works at compile-time.
+    const fn concat(parts: &[&str]) -> &'static str {
+        const fn compiletime(parts: &[&str]) -> &'static str {
+            let mut length = 0;
+            let mut i = 0;
+            while i < parts.len() {
+                length += parts[i].len();
+                i += 1;
+            }
+
+            let output = unsafe {
+                core::slice::from_raw_parts_mut(std::intrinsics::const_allocate(length, 1), length)
+            };
+
+            let mut output_i = 0;
+            let mut i = 0;
+            while i < parts.len() {
+                output_i = unsafe { copy_into_slice_at(output, output_i, parts[i].as_bytes()) };
+                i += 1;
+            }
+
+            assert!(output.len() == output_i);
+            unsafe { str::from_utf8_unchecked_mut(output) }
+        }
+
+        fn runtime(_parts: &[&str]) -> &'static str {
+            only_at_compile_time()
+        }
+
+        std::intrinsics::const_eval_select((parts,), compiletime, runtime)
+    }
+
+    /// `module.name`, with a Rust module path's `::`s as `.`s.
+    ///
+    /// Just like [generic_format], this only works at compile-time.
+    const fn qualified_format(module: &str, name: &str) -> &'static str {
+        /// Does a `::` start at `i`?
+        const fn separator(module: &[u8], i: usize) -> bool {
+            i + 1 < module.len() && module[i] == b':' && module[i + 1] == b':'
+        }
+
+        const fn compiletime(module: &str, name: &str) -> &'static str {
+            let module = module.as_bytes();
+
+            let mut length = module.len() + ".".len() + name.len();
+            let mut i = 0;
+            while i < module.len() {
+                if separator(module, i) {
+                    length -= "::".len() - ".".len();
+                    i += 1;
+                }
+                i += 1;
+            }
+
+            let output = unsafe {
+                core::slice::from_raw_parts_mut(std::intrinsics::const_allocate(length, 1), length)
+            };
+
+            let mut output_i = 0;
+            let mut i = 0;
+            while i < module.len() {
+                match separator(module, i) {
+                    true => {
+                        output[output_i] = b'.';
+                        i += 2;
+                    }
+                    false => {
+                        output[output_i] = module[i];
+                        i += 1;
+                    }
+                }
+                output_i += 1;
+            }
+
+            output_i = unsafe { copy_into_slice_at(output, output_i, b".") };
+            output_i = unsafe { copy_into_slice_at(output, output_i, name.as_bytes()) };
+
+            assert!(output.len() == output_i);
+            unsafe { str::from_utf8_unchecked_mut(output) }
+        }
+
+        fn runtime(_module: &str, _name: &str) -> &'static str {
+            only_at_compile_time()
+        }
+
+        std::intrinsics::const_eval_select((module, name), compiletime, runtime)
+    }
+
+    /// The empty union.
+    const NEVER: &str = "typing.Never";
+
+    /// `module.name`, at runtime.
+    fn write_qualified(f: &mut impl fmt::Write, module: &str, name: &str) -> fmt::Result {
+        write!(f, "{}.{name}", module.replace("::", "."))
+    }
+
+    /// `start[items, ...]`, at runtime.
+    fn write_generic(f: &mut impl fmt::Write, start: &str, items: &[Type<'_>]) -> fmt::Result {
+        write!(f, "{start}[")?;
+
+        for (i, item) in items.iter().enumerate() {
+            if i > 0 {
+                f.write_str(", ")?;
+            }
+
+            item.write_to(f)?;
+        }
+
+        f.write_str("]")
+    }
+
+    /// `A | B | ...`, flattened and without repeats, at runtime.
+    fn write_union(f: &mut impl fmt::Write, items: &[Type<'_>]) -> fmt::Result {
+        let mut seen = Vec::new();
+        for member in Type::union_members(items) {
+            let mut rendered = String::new();
+            member.write_to(&mut rendered)?;
+
+            if !seen.contains(&rendered) {
+                seen.push(rendered);
+            }
+        }
+
+        match seen.is_empty() {
+            true => f.write_str(NEVER),
+            false => f.write_str(&seen.join(" | ")),
+        }
     }
 
     impl InBuilt<'_> {
+        pub fn write_to(&self, f: &mut impl fmt::Write) -> fmt::Result {
+            match self {
+                Tuple(items) => write_generic(f, "tuple", items),
+                List(t) => write_generic(f, "list", core::slice::from_ref(t)),
+                Set(t) => write_generic(f, "set", core::slice::from_ref(t)),
+                Dict(kv) => write_generic(f, "dict", kv.as_slice()),
+                // Just a name.
+                _ => f.write_str(self.as_str()),
+            }
+        }
+
         pub const fn as_str(&self) -> &'static str {
             match self {
                 Ellipses => "...",
@@ -189,82 +604,499 @@ pub mod python {
     #[derive(Debug, PartialEq, Eq)]
     pub enum Typing<'a> {
         Iterator(&'a Type<'a>),
+        Any,
+        Optional(&'a Type<'a>),
+        /// `typing.Literal[values, ...]`, for exactly these values:
+        /// ```
+        /// # use pyly_lib::python::{Literal, Type, Typing};
+        /// const AS: Type = Type::Typing(Typing::Literal(&[Literal::Str("red"), Literal::Int(3)]));
+        /// assert_eq!(AS.to_string(), "typing.Literal['red', 3]");
+        /// ```
+        Literal(&'a [Literal<'a>]),
+        /// `typing.Callable[[params, ...], returns]`
+        Callable {
+            params: &'a [Type<'a>],
+            returns: &'a Type<'a>,
+        },
+    }
+
+    /// A value that can appear in a [Typing::Literal].
+    #[repr(C, u8)]
+    #[derive(Debug, PartialEq, Eq)]
+    pub enum Literal<'a> {
+        Int(i128),
+        Str(&'a str),
+        Bool(bool),
     }
 
     impl Typing<'_> {
+        pub fn write_to(&self, f: &mut impl fmt::Write) -> fmt::Result {
+            match self {
+                Typing::Iterator(t) => {
+                    write_generic(f, "typing.Iterator", core::slice::from_ref(t))
+                }
+                Typing::Any => f.write_str("typing.Any"),
+                Typing::Optional(t) => {
+                    write_generic(f, "typing.Optional", core::slice::from_ref(t))
+                }
+                Typing::Literal(values) => {
+                    f.write_str("typing.Literal[")?;
+                    for (i, value) in values.iter().enumerate() {
+                        if i > 0 {
+                            f.write_str(", ")?;
+                        }
+
+                        value.write_to(f)?;
+                    }
+                    f.write_str("]")
+                }
+                Typing::Callable { params, returns } => {
+                    f.write_str("typing.Callable[")?;
+                    write_generic(f, "", params)?;
+                    f.write_str(", ")?;
+                    returns.write_to(f)?;
+                    f.write_str("]")
+                }
+            }
+        }
+
         pub const fn as_str(&self) -> &'static str {
             match self {
                 Typing::Iterator(t) => generic_format("typing.Iterator", core::slice::from_ref(t)),
+                Typing::Any => "typing.Any",
+                Typing::Optional(t) => generic_format("typing.Optional", core::slice::from_ref(t)),
+                Typing::Literal(values) => literal_format(values),
+                Typing::Callable { params, returns } => concat(&[
+                    "typing.Callable[",
+                    generic_format("", params),
+                    ", ",
+                    returns.as_str(),
+                    "]",
+                ]),
             }
         }
     }
 
-    #[repr(C, u8)]
-    #[derive(Debug, Default, PartialEq, Eq)]
-    pub enum Type<'a> {
-        InBuilt(InBuilt<'a>),
-        Typing(Typing<'a>),
+    /// Which quote Python's `repr` would use for `str`:
+    /// single, unless only those appear within it.
+    const fn quote(str: &str) -> u8 {
+        let bytes = str.as_bytes();
+        let (mut single, mut double) = (false, false);
 
-        #[default]
-        Custom,
+        let mut i = 0;
+        while i < bytes.len() {
+            single |= bytes[i] == b'\'';
+            double |= bytes[i] == b'"';
+            i += 1;
+        }
+
+        match single && !double {
+            true => b'"',
+            false => b'\'',
+        }
     }
 
-    impl Type<'_> {
-        pub const fn as_str(&self) -> &'static str {
-            match self {
-                Type::InBuilt(in_built) => in_built.as_str(),
-                Type::Typing(typing) => typing.as_str(),
-                Custom => "typing.Any",
-            }
+    /// What follows the backslash, if `byte` needs escaping in a string quoted with `quote`.
+    const fn escape(byte: u8, quote: u8) -> Option<u8> {
+        match byte {
+            b'\\' => Some(b'\\'),
+            b'\n' => Some(b'n'),
+            b'\r' => Some(b'r'),
+            b'\t' => Some(b't'),
+            _ if byte == quote => Some(quote),
+            _ => Option::None,
         }
     }
 
-    use std::{
-        collections::{BTreeMap, BTreeSet, HashMap, HashSet},
-        fmt::Write,
-    };
+    impl Literal<'_> {
+        /// Write out this value as Python would, e.g. `'red'`, `3` or `True`.
+        pub fn write_to(&self, f: &mut impl fmt::Write) -> fmt::Result {
+            match self {
+                Literal::Int(int) => write!(f, "{int}"),
+                Literal::Str(str) => {
+                    let quote = quote(str) as char;
 
-    use crate::Exposed;
+                    f.write_char(quote)?;
+                    for c in str.chars() {
+                        match c.is_ascii().then(|| escape(c as u8, quote as u8)).flatten() {
+                            Some(escaped) => write!(f, "\\{}", escaped as char)?,
+                            Option::None => f.write_char(c)?,
+                        }
+                    }
+                    f.write_char(quote)
+                }
+                Literal::Bool(true) => f.write_str("True"),
+                Literal::Bool(false) => f.write_str("False"),
+            }
+        }
 
-    use self::{InBuilt::*, Type::*};
-    use super::Python as Py;
+        /// How long this value is, once written out.
+        const fn len(&self) -> usize {
+            match self {
+                Literal::Int(int) => {
+                    let mut len = if *int < 0 { 2 } else { 1 };
+                    let mut int = int.unsigned_abs() / 10;
+                    while int > 0 {
+                        len += 1;
+                        int /= 10;
+                    }
 
-    impl_lang_for!(Py, [u8, u16, u32, u64, u128, usize] => InBuilt(Int));
-    impl_lang_for!(Py, [i8, i16, i32, i64, i128, isize] => InBuilt(Int));
+                    len
+                }
+                Literal::Str(str) => {
+                    let quote = quote(str);
+                    let bytes = str.as_bytes();
 
-    impl_lang_for!(Py, [f32, f64] => InBuilt(Float));
+                    let mut len = bytes.len() + 2;
+                    let mut i = 0;
+                    while i < bytes.len() {
+                        if escape(bytes[i], quote).is_some() {
+                            len += 1;
+                        }
+                        i += 1;
+                    }
 
-    impl_lang_for!(Py, [bool] => InBuilt(Bool));
+                    len
+                }
+                Literal::Bool(true) => "True".len(),
+                Literal::Bool(false) => "False".len(),
+            }
+        }
 
-    impl_lang_for!(Py, [char, &str, String, std::borrow::Cow<'_, str>, std::rc::Rc<str>, std::sync::Arc<str>, Box<str>] => InBuilt(Str));
+        /// Write out this value into `dest`, at `start`, returning where it ends.
+        ///
+        /// `dest` must have room for [Literal::len] more bytes.
+        const unsafe fn copy_into_slice_at(&self, dest: &mut [u8], start: usize) -> usize {
+            let end = start + self.len();
+            let out = unsafe { slice_mut(dest, start, end) };
 
-    // Tuple-likes
-    impl_lang_for!(Py, [()] => InBuilt(None));
-    impl_tuple!(T1);
-    impl_tuple!(T1, T2);
-    impl_tuple!(T1, T2, T3);
-    impl_tuple!(T1, T2, T3, T4);
-    impl_tuple!(T1, T2, T3, T4, T5);
-    impl_tuple!(T1, T2, T3, T4, T5, T6);
-    impl_tuple!(T1, T2, T3, T4, T5, T6, T7);
-    impl_tuple!(T1, T2, T3, T4, T5, T6, T7, T8);
-    impl_tuple!(T1, T2, T3, T4, T5, T6, T7, T8, T9);
-    impl_tuple!(T1, T2, T3, T4, T5, T6, T7, T8, T9, T10);
-    impl_tuple!(T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11);
-    impl_tuple!(T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11, T12);
+            match self {
+                Literal::Int(int) => {
+                    let mut digits = int.unsigned_abs();
+                    let mut i = out.len();
+                    loop {
+                        i -= 1;
+                        out[i] = b'0' + (digits % 10) as u8;
+                        digits /= 10;
 
-    // List-likes
-    impl<T: Exposed<Py>> Exposed<Py> for [T] {
-        const AS: <Py as crate::Language>::Type = InBuilt(List(&T::AS));
+                        if digits == 0 {
+                            break;
+                        }
+                    }
+
+                    if *int < 0 {
+                        out[0] = b'-';
+                    }
+                }
+                Literal::Str(str) => {
+                    let quote = quote(str);
+                    let bytes = str.as_bytes();
+                    out[0] = quote;
+
+                    let mut o = 1;
+                    let mut i = 0;
+                    while i < bytes.len() {
+                        match escape(bytes[i], quote) {
+                            Some(escaped) => {
+                                out[o] = b'\\';
+                                out[o + 1] = escaped;
+                                o += 2;
+                            }
+                            Option::None => {
+                                out[o] = bytes[i];
+                                o += 1;
+                            }
+                        }
+                        i += 1;
+                    }
+
+                    out[o] = quote;
+                }
+                Literal::Bool(true) => out.copy_from_slice(b"True"),
+                Literal::Bool(false) => out.copy_from_slice(b"False"),
+            }
+
+            end
+        }
     }
 
-    impl<T: Exposed<Py>> Exposed<Py> for Box<[T]> {
-        const AS: <Py as crate::Language>::Type = InBuilt(List(&T::AS));
+    #[repr(C, u8)]
+    #[derive(Debug, PartialEq, Eq)]
+    pub enum Type<'a> {
+        InBuilt(InBuilt<'a>),
+        Typing(Typing<'a>),
+        /// `A | B | ...`
+        ///
+        /// Nested unions are flattened, and repeated members left out,
+        /// when rendered.
+        Union(&'a [Type<'a>]),
+        /// A type variable (declared with `typing.TypeVar`), such as
+        /// a generic parameter of a type defined in Rust.
+        TypeVar(&'a str),
+        /// `returns`, from something which may raise `raises` instead
+        /// (i.e. a [Result]).
+        ///
+        /// Annotated as just `returns`, with `raises` noted separately.
+        Raises {
+            returns: &'a Type<'a>,
+            raises: &'a Type<'a>,
+        },
+
+        /// A type defined in Rust (and exposed itself), or in Python,
+        /// referred to by its module's path and its name:
+        /// ```ignore
+        /// Type::Custom {
+        ///     module: module_path!(),
+        ///     name: "MyCoolType",
+        /// }
+        /// ```
+        Custom {
+            module: &'a str,
+            name: &'a str,
+        },
+        /// A class declared in the stubs themselves, for a type marked with
+        /// `#[pyly::expose]` (whose [Exposed] impl is generated as this),
+        /// referred to by its module, its name, and any type arguments.
+        ///
+        /// The module is either a Rust module path (from `module_path!()`),
+        /// or the dotted module given with `#[pyly::expose(module = "...")]`.
+        ///
+        /// It's also how to refer to a generic class from elsewhere,
+        /// such as `os.PathLike[str]`.
+        Class {
+            module: &'a str,
+            name: &'a str,
+            args: &'a [Type<'a>],
+        },
+    }
+
+    impl Default for Type<'_> {
+        fn default() -> Self {
+            Type::Typing(Typing::Any)
+        }
+    }
+
+    impl<'a> Type<'a> {
+        /// Write out this type, e.g. into a [String] or [fmt::Formatter].
+        ///
+        /// Unlike [Type::as_str], this works at runtime.
+        pub fn write_to(&self, f: &mut impl fmt::Write) -> fmt::Result {
+            match self {
+                Type::InBuilt(in_built) => in_built.write_to(f),
+                Type::Typing(typing) => typing.write_to(f),
+                Type::Union(items) => write_union(f, items),
+                Raises { returns, .. } => returns.write_to(f),
+                Custom { module, name }
+                | Class {
+                    module,
+                    name,
+                    args: [],
+                } => write_qualified(f, module, name),
+                Class { module, name, args } => {
+                    write_qualified(f, module, name)?;
+                    write_generic(f, "", args)
+                }
+                TypeVar(name) => f.write_str(name),
+            }
+        }
+
+        /// The members of a union of `items`, with nested unions flattened,
+        /// and without any repeats.
+        pub fn union_members(items: &'a [Type<'a>]) -> Vec<&'a Type<'a>> {
+            crate::union_members(items, |item| match item {
+                Type::Union(inner) => Some(inner),
+                _ => Option::None,
+            })
+        }
+
+        /// This type's name, for use at compile-time:
+        /// ```
+        /// # use pyly_lib::{Exposed, Python};
+        /// const NAME: &str = <Vec<u16> as Exposed<Python>>::AS.as_str();
+        /// assert_eq!(NAME, "list[int]");
+        /// ```
+        /// At runtime, this panics: use [fmt::Display] (or [Type::write_to]) there.
+        pub const fn as_str(&self) -> &'a str {
+            match self {
+                Type::InBuilt(in_built) => in_built.as_str(),
+                Type::Typing(typing) => typing.as_str(),
+                Type::Union(items) => union_format(items),
+                Raises { returns, .. } => returns.as_str(),
+                Custom { module, name }
+                | Class {
+                    module,
+                    name,
+                    args: [],
+                } => qualified_format(module, name),
+                Class { module, name, args } => {
+                    generic_format(qualified_format(module, name), args)
+                }
+                TypeVar(name) => name,
+            }
+        }
+    }
+
+    impl fmt::Display for InBuilt<'_> {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            self.write_to(f)
+        }
+    }
+
+    impl fmt::Display for Typing<'_> {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            self.write_to(f)
+        }
+    }
+
+    impl fmt::Display for Literal<'_> {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            self.write_to(f)
+        }
+    }
+
+    impl fmt::Display for Type<'_> {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            self.write_to(f)
+        }
+    }
+
+    use std::{
+        borrow::Cow,
+        cell::{Cell, RefCell},
+        collections::{BTreeMap, BTreeSet, BinaryHeap, HashMap, HashSet, LinkedList, VecDeque},
+        fmt,
+        path::{Path, PathBuf},
+        rc::Rc,
+        sync::{Arc, Mutex, RwLock},
+        time::{Duration, SystemTime},
+    };
+
+    use crate::Exposed;
+
+    use self::{InBuilt::*, Type::*};
+    use super::Python as Py;
+
+    impl Exposed<Py> for u8 {
+        const AS: <Py as crate::Language>::Type = InBuilt(Int);
+        const AS_LIST: Option<<Py as crate::Language>::Type> = Some(InBuilt(Bytes));
+    }
+    impl_lang_for!(Py, [u16, u32, u64, u128, usize] => InBuilt(Int));
+    impl_lang_for!(Py, [i8, i16, i32, i64, i128, isize] => InBuilt(Int));
+
+    impl_lang_for!(Py, [f32, f64] => InBuilt(Float));
+
+    impl_lang_for!(Py, [bool] => InBuilt(Bool));
+
+    // `&str`, `Box<str>`, `Cow<'_, str>`, ... are covered by the impls for pointers.
+    impl_lang_for!(Py, [char, str, String] => InBuilt(Str));
+
+    // Tuple-likes
+    impl_lang_for!(Py, [()] => InBuilt(None));
+    impl_tuple!(T1);
+    impl_tuple!(T1, T2);
+    impl_tuple!(T1, T2, T3);
+    impl_tuple!(T1, T2, T3, T4);
+    impl_tuple!(T1, T2, T3, T4, T5);
+    impl_tuple!(T1, T2, T3, T4, T5, T6);
+    impl_tuple!(T1, T2, T3, T4, T5, T6, T7);
+    impl_tuple!(T1, T2, T3, T4, T5, T6, T7, T8);
+    impl_tuple!(T1, T2, T3, T4, T5, T6, T7, T8, T9);
+    impl_tuple!(T1, T2, T3, T4, T5, T6, T7, T8, T9, T10);
+    impl_tuple!(T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11);
+    impl_tuple!(T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11, T12);
+
+    // Pointers (and the like) are just what they point to.
+    impl<T: Exposed<Py> + ?Sized> Exposed<Py> for &T {
+        const AS: <Py as crate::Language>::Type = T::AS;
+    }
+    impl<T: Exposed<Py> + ?Sized> Exposed<Py> for &mut T {
+        const AS: <Py as crate::Language>::Type = T::AS;
+    }
+    impl<T: Exposed<Py> + ?Sized> Exposed<Py> for Box<T> {
+        const AS: <Py as crate::Language>::Type = T::AS;
+    }
+    impl<T: Exposed<Py> + ?Sized> Exposed<Py> for Rc<T> {
+        const AS: <Py as crate::Language>::Type = T::AS;
+    }
+    impl<T: Exposed<Py> + ?Sized> Exposed<Py> for Arc<T> {
+        const AS: <Py as crate::Language>::Type = T::AS;
+    }
+    impl<T: Exposed<Py> + ToOwned + ?Sized> Exposed<Py> for Cow<'_, T> {
+        const AS: <Py as crate::Language>::Type = T::AS;
+    }
+
+    // Interior mutability doesn't show.
+    impl<T: Exposed<Py> + ?Sized> Exposed<Py> for Cell<T> {
+        const AS: <Py as crate::Language>::Type = T::AS;
+    }
+    impl<T: Exposed<Py> + ?Sized> Exposed<Py> for RefCell<T> {
+        const AS: <Py as crate::Language>::Type = T::AS;
+    }
+    impl<T: Exposed<Py> + ?Sized> Exposed<Py> for Mutex<T> {
+        const AS: <Py as crate::Language>::Type = T::AS;
+    }
+    impl<T: Exposed<Py> + ?Sized> Exposed<Py> for RwLock<T> {
+        const AS: <Py as crate::Language>::Type = T::AS;
     }
 
+    // List-likes (though a `Vec<u8>` or `[u8]` is `bytes`)
+    impl<T: Exposed<Py>> Exposed<Py> for [T] {
+        const AS: <Py as crate::Language>::Type = list::<T>();
+    }
+    impl<T: Exposed<Py>, const N: usize> Exposed<Py> for [T; N] {
+        const AS: <Py as crate::Language>::Type = InBuilt(List(&T::AS));
+    }
     impl<T: Exposed<Py>> Exposed<Py> for Vec<T> {
+        const AS: <Py as crate::Language>::Type = list::<T>();
+    }
+    impl<T: Exposed<Py>> Exposed<Py> for VecDeque<T> {
         const AS: <Py as crate::Language>::Type = InBuilt(List(&T::AS));
     }
+    impl<T: Exposed<Py>> Exposed<Py> for LinkedList<T> {
+        const AS: <Py as crate::Language>::Type = InBuilt(List(&T::AS));
+    }
+    impl<T: Exposed<Py>> Exposed<Py> for BinaryHeap<T> {
+        const AS: <Py as crate::Language>::Type = InBuilt(List(&T::AS));
+    }
+
+    const fn list<T: Exposed<Py>>() -> Type<'static> {
+        match T::AS_LIST {
+            Some(list) => list,
+            Option::None => InBuilt(List(&T::AS)),
+        }
+    }
+
+    // Paths can be given as either.
+    impl_lang_for!(Py, [Path, PathBuf] => Type::Union(&[InBuilt(Str), OS_PATH_LIKE]));
+
+    const OS_PATH_LIKE: Type<'static> = Class {
+        module: "os",
+        name: "PathLike",
+        args: &[InBuilt(Str)],
+    };
+
+    // Wrappers, for the built-ins nothing else maps to.
+    impl<T> Exposed<Py> for crate::Bytes<T> {
+        const AS: <Py as crate::Language>::Type = InBuilt(Bytes);
+    }
+    impl<T> Exposed<Py> for crate::ByteArray<T> {
+        const AS: <Py as crate::Language>::Type = InBuilt(ByteArray);
+    }
+    impl<T> Exposed<Py> for crate::Complex<T> {
+        const AS: <Py as crate::Language>::Type = InBuilt(Complex);
+    }
+    impl<T: Exposed<Py>> Exposed<Py> for crate::TupleOf<T> {
+        const AS: <Py as crate::Language>::Type = InBuilt(Tuple(&[T::AS, InBuilt(Ellipses)]));
+    }
+
+    #[cfg(feature = "num-complex")]
+    impl<T> Exposed<Py> for num_complex::Complex<T> {
+        const AS: <Py as crate::Language>::Type = InBuilt(Complex);
+    }
+
+    // Times
+    impl_lang_for!(Py, [Duration] => Custom { module: "datetime", name: "timedelta" });
+    impl_lang_for!(Py, [SystemTime] => Custom { module: "datetime", name: "datetime" });
 
     // Set-likes
     impl<T: Exposed<Py>> Exposed<Py> for HashSet<T> {
@@ -282,15 +1114,41 @@ pub mod python {
         const AS: <Py as crate::Language>::Type = InBuilt(Dict(&[K::AS, V::AS]));
     }
 
-    impl<I: Exposed<Py>> Exposed<Py> for Box<dyn Iterator<Item = I>> {
+    impl<T: Exposed<Py>> Exposed<Py> for Option<T> {
+        const AS: <Py as crate::Language>::Type = Typing(Typing::Optional(&T::AS));
+    }
+
+    impl<T: Exposed<Py>, E: Exposed<Py>> Exposed<Py> for Result<T, E> {
+        const AS: <Py as crate::Language>::Type = Raises {
+            returns: &T::AS,
+            raises: &E::AS,
+        };
+    }
+
+    impl<I: Exposed<Py>> Exposed<Py> for dyn Iterator<Item = I> {
         const AS: <Py as crate::Language>::Type = Typing(Typing::Iterator(&I::AS));
     }
 
+    // Callables
+    impl_callable!();
+    impl_callable!(A1);
+    impl_callable!(A1, A2);
+    impl_callable!(A1, A2, A3);
+    impl_callable!(A1, A2, A3, A4);
+    impl_callable!(A1, A2, A3, A4, A5);
+    impl_callable!(A1, A2, A3, A4, A5, A6);
+
     #[cfg(test)]
     mod tests {
         use std::{
-            collections::{BTreeMap, HashMap, HashSet},
+            borrow::Cow,
+            cell::{Cell, RefCell},
+            collections::{BTreeMap, BinaryHeap, HashMap, HashSet, LinkedList, VecDeque},
             mem,
+            path::{Path, PathBuf},
+            rc::Rc,
+            sync::{Arc, Mutex, RwLock},
+            time::{Duration, SystemTime},
         };
 
         use crate::{
@@ -298,6 +1156,15 @@ pub mod python {
             python::{self, Type},
         };
 
+        struct Custom;
+
+        impl Exposed<Python> for Custom {
+            const AS: Type<'static> = Type::Custom {
+                module: module_path!(),
+                name: "Custom",
+            };
+        }
+
         #[test]
         fn compound_types() {
             const A: &str = {
@@ -321,11 +1188,692 @@ pub mod python {
             println!("{A}");
         }
 
+        #[test]
+        fn optional() {
+            const A: &str = <Option<Vec<Option<u8>>> as Exposed<Python>>::AS.as_str();
+            assert_eq!(A, "typing.Optional[list[typing.Optional[int]]]");
+        }
+
+        /// `<T as Exposed<Python>>::AS.as_str()`
+        macro_rules! py {
+            ($t: ty) => {
+                const { <$t as Exposed<Python>>::AS.as_str() }
+            };
+        }
+
+        #[test]
+        fn results() {
+            const RESULT: Type = <Result<u8, Custom> as Exposed<Python>>::AS;
+            assert!(matches!(
+                RESULT,
+                Type::Raises {
+                    returns: Type::InBuilt(python::InBuilt::Int),
+                    raises: Type::Custom { name: "Custom", .. },
+                }
+            ));
+
+            // Only what's returned shows in annotations.
+            assert_eq!(py!(Result<crate::Bytes, String>), "bytes");
+            assert_eq!(py!(Option<Result<bool, Custom>>), "typing.Optional[bool]");
+            assert_eq!(RESULT.to_string(), "int");
+        }
+
+        #[test]
+        fn list_likes() {
+            assert_eq!(py!(VecDeque<u8>), "list[int]");
+            assert_eq!(py!(LinkedList<String>), "list[str]");
+            assert_eq!(py!(BinaryHeap<(u8, bool)>), "list[tuple[int, bool]]");
+        }
+
+        #[test]
+        fn arrays() {
+            assert_eq!(py!([u16; 4]), "list[int]");
+            assert_eq!(py!([[f32; 2]; 2]), "list[list[float]]");
+        }
+
+        #[test]
+        fn bytes() {
+            assert_eq!(py!(crate::Bytes), "bytes");
+            assert_eq!(py!(crate::Bytes<&[u8]>), "bytes");
+            assert_eq!(py!(Vec<crate::Bytes>), "list[bytes]");
+            assert_eq!(py!(Vec<u8>), "bytes");
+            assert_eq!(py!(&[u8]), "bytes");
+            assert_eq!(py!(Box<[u8]>), "bytes");
+            assert_eq!(py!(Vec<Vec<u8>>), "list[bytes]");
+            // Only `u8`s, and only in a `Vec` or slice, are `bytes`.
+            assert_eq!(py!(Vec<i8>), "list[int]");
+            assert_eq!(py!([u8; 4]), "list[int]");
+        }
+
+        #[test]
+        fn pointers() {
+            assert_eq!(py!(Box<u8>), "int");
+            assert_eq!(py!(Rc<str>), "str");
+            assert_eq!(py!(Arc<Vec<bool>>), "list[bool]");
+            assert_eq!(py!(&'static str), "str");
+            assert_eq!(py!(&'static mut Custom), "pyly_lib.python.tests.Custom");
+            assert_eq!(py!(Box<[String]>), "list[str]");
+            assert_eq!(py!(Box<dyn Iterator<Item = u8>>), "typing.Iterator[int]");
+        }
+
+        #[test]
+        fn cows() {
+            assert_eq!(py!(Cow<'static, str>), "str");
+            assert_eq!(py!(Cow<'static, [u16]>), "list[int]");
+        }
+
+        #[test]
+        fn cells() {
+            assert_eq!(py!(Cell<u8>), "int");
+            assert_eq!(py!(RefCell<Vec<String>>), "list[str]");
+            assert_eq!(py!(Arc<Mutex<f64>>), "float");
+            assert_eq!(py!(RwLock<Option<bool>>), "typing.Optional[bool]");
+        }
+
+        #[test]
+        fn paths() {
+            assert_eq!(py!(PathBuf), "str | os.PathLike[str]");
+            assert_eq!(py!(&'static Path), "str | os.PathLike[str]");
+
+            let path = <PathBuf as Exposed<Python>>::AS;
+            assert_eq!(
+                path,
+                Type::Union(&[
+                    Type::InBuilt(python::InBuilt::Str),
+                    Type::Class {
+                        module: "os",
+                        name: "PathLike",
+                        args: &[Type::InBuilt(python::InBuilt::Str)],
+                    }
+                ])
+            );
+            assert_eq!(path.to_string(), "str | os.PathLike[str]");
+        }
+
+        #[test]
+        fn times() {
+            const DURATION: Type = <Duration as Exposed<Python>>::AS;
+            const SYSTEM_TIME: Type = <SystemTime as Exposed<Python>>::AS;
+
+            assert!(matches!(
+                DURATION,
+                Type::Custom {
+                    module: "datetime",
+                    name: "timedelta"
+                }
+            ));
+            assert!(matches!(
+                SYSTEM_TIME,
+                Type::Custom {
+                    module: "datetime",
+                    name: "datetime"
+                }
+            ));
+        }
+
+        #[test]
+        fn wrappers() {
+            use crate::{ByteArray, Bytes, Complex, TupleOf};
+
+            assert_eq!(py!(Bytes<[u8; 4]>), "bytes");
+            assert_eq!(py!(ByteArray), "bytearray");
+            assert_eq!(py!(Vec<ByteArray<String>>), "list[bytearray]");
+            assert_eq!(py!(Complex<(f32, f32)>), "complex");
+            #[cfg(feature = "num-complex")]
+            assert_eq!(py!(num_complex::Complex<f64>), "complex");
+            assert_eq!(py!(TupleOf<u8>), "tuple[int, ...]");
+            assert_eq!(
+                py!(TupleOf<Option<String>>),
+                "tuple[typing.Optional[str], ...]"
+            );
+        }
+
+        #[test]
+        fn custom_types() {
+            const A: &str = <HashMap<String, Custom> as Exposed<Python>>::AS.as_str();
+            assert_eq!(A, "dict[str, pyly_lib.python.tests.Custom]");
+        }
+
+        #[test]
+        fn classes() {
+            /// As `#[pyly::expose]` would implement it.
+            struct Paged<T>(T);
+
+            impl<T: Exposed<Python>> Exposed<Python> for Paged<T> {
+                const AS: Type<'static> = Type::Class {
+                    module: module_path!(),
+                    name: "Paged",
+                    args: &[T::AS],
+                };
+            }
+
+            assert_eq!(py!(Paged<u8>), "pyly_lib.python.tests.Paged[int]");
+            assert_eq!(
+                py!(Vec<Paged<Option<bool>>>),
+                "list[pyly_lib.python.tests.Paged[typing.Optional[bool]]]"
+            );
+
+            let ty = <Paged<Vec<String>> as Exposed<Python>>::AS;
+            assert_eq!(ty.to_string(), "pyly_lib.python.tests.Paged[list[str]]");
+        }
+
+        #[test]
+        fn unions() {
+            const STR: Type = <String as Exposed<Python>>::AS;
+            const CUSTOM: Type = <Custom as Exposed<Python>>::AS;
+            const INT_OR_STR: Type = Type::Union(&[<u8 as Exposed<Python>>::AS, STR]);
+
+            const A: &str = Type::Union(&[INT_OR_STR, CUSTOM, STR, INT_OR_STR]).as_str();
+            assert_eq!(A, "int | str | pyly_lib.python.tests.Custom");
+
+            const B: Type = Type::Union(&[<Vec<()> as Exposed<Python>>::AS, Type::Union(&[])]);
+            const B_STR: &str = B.as_str();
+            assert_eq!(B_STR, "list[None]");
+            assert_eq!(B.to_string(), "list[None]");
+
+            const NEVER: &str = Type::Union(&[Type::Union(&[])]).as_str();
+            assert_eq!(NEVER, "typing.Never");
+
+            let a = Type::Union(&[INT_OR_STR, CUSTOM, STR, INT_OR_STR]);
+            assert_eq!(a.to_string(), A);
+        }
+
+        #[test]
+        fn literals() {
+            use python::{Literal, Typing};
+
+            const VALUES: &[Literal] = &[
+                Literal::Int(0),
+                Literal::Int(-128),
+                Literal::Int(i128::MIN),
+                Literal::Str("it's \\o/"),
+                Literal::Str("'\"\n"),
+                Literal::Str(""),
+                Literal::Bool(true),
+                Literal::Bool(false),
+            ];
+            const EXPECTED: &str = "typing.Literal[0, -128, -170141183460469231731687303715884105728, \"it's \\\\o/\", '\\'\"\\n', '', True, False]";
+
+            const A: &str = Type::Typing(Typing::Literal(VALUES)).as_str();
+            assert_eq!(A, EXPECTED);
+            assert_eq!(Type::Typing(Typing::Literal(VALUES)).to_string(), EXPECTED);
+        }
+
+        #[test]
+        fn type_params() {
+            use crate::TypeParam;
+
+            const A: &str =
+                <HashMap<TypeParam<"K">, Vec<TypeParam<"V">>> as Exposed<Python>>::AS.as_str();
+            assert_eq!(A, "dict[K, list[V]]");
+        }
+
+        #[test]
+        fn callables() {
+            const A: &str = <fn(u8) -> String as Exposed<Python>>::AS.as_str();
+            assert_eq!(A, "typing.Callable[[int], str]");
+
+            const B: &str = <Box<dyn Fn()> as Exposed<Python>>::AS.as_str();
+            assert_eq!(B, "typing.Callable[[], None]");
+
+            type C<'a> = &'a mut dyn FnMut(Vec<bool>, fn(f64) -> bool) -> Option<u8>;
+            const C: &str = <C as Exposed<Python>>::AS.as_str();
+            let c = <C as Exposed<Python>>::AS.to_string();
+            assert_eq!(
+                C,
+                "typing.Callable[[list[bool], typing.Callable[[float], bool]], typing.Optional[int]]"
+            );
+            assert_eq!(c, C);
+        }
+
+        #[test]
+        fn display() {
+            type Nested = HashMap<(u8, Option<String>), Vec<Box<dyn Iterator<Item = Custom>>>>;
+
+            const A: &str = <Nested as Exposed<Python>>::AS.as_str();
+            let a = <Nested as Exposed<Python>>::AS.to_string();
+
+            assert_eq!(
+                a,
+                "dict[tuple[int, typing.Optional[str]], list[typing.Iterator[pyly_lib.python.tests.Custom]]]"
+            );
+            assert_eq!(a, A);
+        }
+
+        #[test]
+        #[should_panic(expected = "use `Display`")]
+        fn as_str_at_runtime() {
+            let ty = <Vec<(u8, bool)> as Exposed<Python>>::AS;
+            assert_eq!(ty.to_string(), "list[tuple[int, bool]]");
+
+            ty.as_str();
+        }
+
         #[test]
         fn mem_layout() {
             println!("{:?}", unsafe {
-                mem::transmute::<Type, [[u8; 8]; 4]>(python::Custom)
+                mem::transmute::<Type, [[u8; 8]; mem::size_of::<Type>() / 8]>(python::Custom {
+                    module: module_path!(),
+                    name: "Custom",
+                })
             });
         }
     }
 }
+
+pub mod typescript {
+    macro_rules! impl_tuple {
+        ($($ti: ident),*) => {
+            #[doc = "This trait is implemented for tuples up to twelve items long."]
+            impl<$($ti : crate::Exposed<Ts>),*,> crate::Exposed<Ts> for ($($ti),*, ) {
+                const AS: <Ts as $crate::Language>::Type = Tuple(&[$($ti ::AS),*]);
+            }
+        }
+    }
+
+    #[repr(C, u8)]
+    #[derive(Debug, Default, PartialEq, Eq)]
+    pub enum Type<'a> {
+        Null,
+        Boolean,
+        Number,
+        BigInt,
+        String,
+        #[default]
+        Unknown,
+        /// `Array<T>`
+        Array(&'a Type<'a>),
+        /// `[A, B, ...]`
+        Tuple(&'a [Type<'a>]),
+        /// `Record<K, V>`, for plain objects used as maps.
+        Record(&'a [Type<'a>; 2]),
+        Set(&'a Type<'a>),
+        Map(&'a [Type<'a>; 2]),
+        Iterator(&'a Type<'a>),
+        /// `A | B | ...`
+        Union(&'a [Type<'a>]),
+        /// A type parameter, such as a generic parameter of a type defined in Rust.
+        Parameter(&'a str),
+        /// `returns`, from something which may throw `throws` instead
+        /// (i.e. a [Result]).
+        ///
+        /// Annotated as just `returns`, with `throws` noted in a `@throws` tag.
+        Throws {
+            returns: &'a Type<'a>,
+            throws: &'a Type<'a>,
+        },
+
+        /// An interface for a type defined in Rust (and exposed itself),
+        /// referred to by its module's path and its name
+        /// (or, with an empty module, a global such as `RangeError`):
+        /// ```ignore
+        /// Type::Interface {
+        ///     module: module_path!(),
+        ///     name: "MyCoolType",
+        /// }
+        /// ```
+        Interface {
+            module: &'a str,
+            name: &'a str,
+        },
+        /// An interface declared in the stubs themselves, for a type marked with
+        /// `#[pyly::expose]` (whose [Exposed] impl is generated as this),
+        /// referred to by its module, its name, and any type arguments.
+        ///
+        /// The module is either a Rust module path (from `module_path!()`),
+        /// or the dotted module given with `#[pyly::expose(module = "...")]`.
+        Class {
+            module: &'a str,
+            name: &'a str,
+            args: &'a [Type<'a>],
+        },
+    }
+
+    impl<'a> Type<'a> {
+        /// The members of a union of `items`, with nested unions flattened,
+        /// and without any repeats.
+        pub fn union_members(items: &'a [Type<'a>]) -> Vec<&'a Type<'a>> {
+            crate::union_members(items, |item| match item {
+                Union(inner) => Some(inner),
+                _ => None,
+            })
+        }
+    }
+
+    impl fmt::Display for Type<'_> {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            fn list(f: &mut fmt::Formatter<'_>, items: &[Type<'_>], sep: &str) -> fmt::Result {
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(sep)?;
+                    }
+                    write!(f, "{item}")?;
+                }
+                Ok(())
+            }
+
+            match self {
+                Null => f.write_str("null"),
+                Boolean => f.write_str("boolean"),
+                Number => f.write_str("number"),
+                BigInt => f.write_str("bigint"),
+                String => f.write_str("string"),
+                Unknown => f.write_str("unknown"),
+                Array(t) => write!(f, "Array<{t}>"),
+                Tuple(items) => {
+                    f.write_str("[")?;
+                    list(f, items, ", ")?;
+                    f.write_str("]")
+                }
+                Record([k, v]) => write!(f, "Record<{k}, {v}>"),
+                Set(t) => write!(f, "Set<{t}>"),
+                Map([k, v]) => write!(f, "Map<{k}, {v}>"),
+                Iterator(t) => write!(f, "Iterator<{t}>"),
+                Union(items) => {
+                    // Repeats are left out by what they render as, too.
+                    let mut seen = Vec::new();
+                    for member in Type::union_members(items) {
+                        let rendered = member.to_string();
+                        if !seen.contains(&rendered) {
+                            seen.push(rendered);
+                        }
+                    }
+
+                    match seen.is_empty() {
+                        true => f.write_str("never"),
+                        false => f.write_str(&seen.join(" | ")),
+                    }
+                }
+                Throws { returns, .. } => write!(f, "{returns}"),
+                Parameter(name) | Interface { name, .. } | Class { name, args: [], .. } => {
+                    f.write_str(name)
+                }
+                Class { name, args, .. } => {
+                    write!(f, "{name}<")?;
+                    list(f, args, ", ")?;
+                    f.write_str(">")
+                }
+            }
+        }
+    }
+
+    use std::{
+        borrow::Cow,
+        cell::{Cell, RefCell},
+        collections::{BTreeMap, BTreeSet, BinaryHeap, HashMap, HashSet, LinkedList, VecDeque},
+        fmt,
+        path::{Path, PathBuf},
+        rc::Rc,
+        sync::{Arc, Mutex, RwLock},
+        time::{Duration, SystemTime},
+    };
+
+    use crate::Exposed;
+
+    use self::Type::*;
+    use super::TypeScript as Ts;
+
+    // Beyond 2^53, numbers lose precision, but `bigint`s are a pain to use...
+    impl_lang_for!(Ts, [u8, u16, u32, u64, usize] => Number);
+    impl_lang_for!(Ts, [i8, i16, i32, i64, isize] => Number);
+    impl_lang_for!(Ts, [u128, i128] => BigInt);
+
+    impl_lang_for!(Ts, [f32, f64] => Number);
+
+    impl_lang_for!(Ts, [bool] => Boolean);
+
+    // `&str`, `Box<str>`, `Cow<'_, str>`, ... are covered by the impls for pointers.
+    impl_lang_for!(Ts, [char, str, std::string::String] => String);
+
+    // Tuple-likes
+    impl_lang_for!(Ts, [()] => Null);
+    impl_tuple!(T1);
+    impl_tuple!(T1, T2);
+    impl_tuple!(T1, T2, T3);
+    impl_tuple!(T1, T2, T3, T4);
+    impl_tuple!(T1, T2, T3, T4, T5);
+    impl_tuple!(T1, T2, T3, T4, T5, T6);
+    impl_tuple!(T1, T2, T3, T4, T5, T6, T7);
+    impl_tuple!(T1, T2, T3, T4, T5, T6, T7, T8);
+    impl_tuple!(T1, T2, T3, T4, T5, T6, T7, T8, T9);
+    impl_tuple!(T1, T2, T3, T4, T5, T6, T7, T8, T9, T10);
+    impl_tuple!(T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11);
+    impl_tuple!(T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11, T12);
+
+    // Pointers (and the like) are just what they point to.
+    impl<T: Exposed<Ts> + ?Sized> Exposed<Ts> for &T {
+        const AS: <Ts as crate::Language>::Type = T::AS;
+    }
+    impl<T: Exposed<Ts> + ?Sized> Exposed<Ts> for &mut T {
+        const AS: <Ts as crate::Language>::Type = T::AS;
+    }
+    impl<T: Exposed<Ts> + ?Sized> Exposed<Ts> for Box<T> {
+        const AS: <Ts as crate::Language>::Type = T::AS;
+    }
+    impl<T: Exposed<Ts> + ?Sized> Exposed<Ts> for Rc<T> {
+        const AS: <Ts as crate::Language>::Type = T::AS;
+    }
+    impl<T: Exposed<Ts> + ?Sized> Exposed<Ts> for Arc<T> {
+        const AS: <Ts as crate::Language>::Type = T::AS;
+    }
+    impl<T: Exposed<Ts> + ToOwned + ?Sized> Exposed<Ts> for Cow<'_, T> {
+        const AS: <Ts as crate::Language>::Type = T::AS;
+    }
+
+    // Interior mutability doesn't show.
+    impl<T: Exposed<Ts> + ?Sized> Exposed<Ts> for Cell<T> {
+        const AS: <Ts as crate::Language>::Type = T::AS;
+    }
+    impl<T: Exposed<Ts> + ?Sized> Exposed<Ts> for RefCell<T> {
+        const AS: <Ts as crate::Language>::Type = T::AS;
+    }
+    impl<T: Exposed<Ts> + ?Sized> Exposed<Ts> for Mutex<T> {
+        const AS: <Ts as crate::Language>::Type = T::AS;
+    }
+    impl<T: Exposed<Ts> + ?Sized> Exposed<Ts> for RwLock<T> {
+        const AS: <Ts as crate::Language>::Type = T::AS;
+    }
+
+    // Array-likes
+    impl<T: Exposed<Ts>> Exposed<Ts> for [T] {
+        const AS: <Ts as crate::Language>::Type = Array(&T::AS);
+    }
+    impl<T: Exposed<Ts>, const N: usize> Exposed<Ts> for [T; N] {
+        const AS: <Ts as crate::Language>::Type = Array(&T::AS);
+    }
+    impl<T: Exposed<Ts>> Exposed<Ts> for Vec<T> {
+        const AS: <Ts as crate::Language>::Type = Array(&T::AS);
+    }
+    impl<T: Exposed<Ts>> Exposed<Ts> for VecDeque<T> {
+        const AS: <Ts as crate::Language>::Type = Array(&T::AS);
+    }
+    impl<T: Exposed<Ts>> Exposed<Ts> for LinkedList<T> {
+        const AS: <Ts as crate::Language>::Type = Array(&T::AS);
+    }
+    impl<T: Exposed<Ts>> Exposed<Ts> for BinaryHeap<T> {
+        const AS: <Ts as crate::Language>::Type = Array(&T::AS);
+    }
+
+    impl_lang_for!(Ts, [Path, PathBuf] => String);
+
+    // Set-likes
+    impl<T: Exposed<Ts>> Exposed<Ts> for HashSet<T> {
+        const AS: <Ts as crate::Language>::Type = Set(&T::AS);
+    }
+    impl<T: Exposed<Ts>> Exposed<Ts> for BTreeSet<T> {
+        const AS: <Ts as crate::Language>::Type = Set(&T::AS);
+    }
+
+    // Map-likes
+    impl<K: Exposed<Ts>, V: Exposed<Ts>> Exposed<Ts> for HashMap<K, V> {
+        const AS: <Ts as crate::Language>::Type = Map(&[K::AS, V::AS]);
+    }
+    impl<K: Exposed<Ts>, V: Exposed<Ts>> Exposed<Ts> for BTreeMap<K, V> {
+        const AS: <Ts as crate::Language>::Type = Map(&[K::AS, V::AS]);
+    }
+
+    impl<T: Exposed<Ts>> Exposed<Ts> for Option<T> {
+        const AS: <Ts as crate::Language>::Type = Union(&[T::AS, Null]);
+    }
+
+    impl<T: Exposed<Ts>, E: Exposed<Ts>> Exposed<Ts> for Result<T, E> {
+        const AS: <Ts as crate::Language>::Type = Throws {
+            returns: &T::AS,
+            throws: &E::AS,
+        };
+    }
+
+    impl<I: Exposed<Ts>> Exposed<Ts> for dyn std::iter::Iterator<Item = I> {
+        const AS: <Ts as crate::Language>::Type = Iterator(&I::AS);
+    }
+
+    // Times, as JavaScript has them: a number (say, of milliseconds), and a `Date`.
+    impl_lang_for!(Ts, [Duration] => Number);
+    impl_lang_for!(Ts, [SystemTime] => Interface { module: "", name: "Date" });
+
+    #[cfg(test)]
+    mod tests {
+        use std::collections::{BTreeMap, HashMap, HashSet};
+
+        use crate::{Exposed, TypeScript, typescript::Type};
+
+        struct Custom;
+
+        impl Exposed<TypeScript> for Custom {
+            const AS: Type<'static> = Type::Interface {
+                module: module_path!(),
+                name: "Custom",
+            };
+        }
+
+        #[test]
+        fn compound_types() {
+            let ty = <Box<
+                dyn Iterator<
+                    Item = (
+                        u8,
+                        u128,
+                        f64,
+                        (
+                            String,
+                            &'static str,
+                            HashMap<(HashSet<usize>, String), BTreeMap<String, usize>>,
+                        ),
+                    ),
+                >,
+            > as Exposed<TypeScript>>::AS;
+
+            assert_eq!(
+                ty.to_string(),
+                "Iterator<[number, bigint, number, [string, string, \
+                 Map<[Set<number>, string], Map<string, number>>]]>"
+            );
+        }
+
+        #[test]
+        fn optional() {
+            let ty = <Option<Vec<Option<u8>>> as Exposed<TypeScript>>::AS;
+            assert_eq!(ty.to_string(), "Array<number | null> | null");
+        }
+
+        #[test]
+        fn results() {
+            let ty = <Result<Vec<u8>, Custom> as Exposed<TypeScript>>::AS;
+            assert!(matches!(
+                ty,
+                Type::Throws {
+                    returns: Type::Array(Type::Number),
+                    throws: Type::Interface { name: "Custom", .. },
+                }
+            ));
+
+            // Only what's returned shows in annotations.
+            assert_eq!(ty.to_string(), "Array<number>");
+        }
+
+        #[test]
+        fn unions() {
+            let ty = <Option<Option<u8>> as Exposed<TypeScript>>::AS;
+            assert_eq!(ty.to_string(), "number | null");
+
+            const NESTED: Type =
+                Type::Union(&[Type::Union(&[Type::Number, Type::String]), Type::Number]);
+            assert_eq!(NESTED.to_string(), "number | string");
+            assert_eq!(Type::Union(&[Type::Union(&[])]).to_string(), "never");
+        }
+
+        #[test]
+        fn iterators() {
+            let ty = <&mut dyn Iterator<Item = u8> as Exposed<TypeScript>>::AS;
+            assert_eq!(ty.to_string(), "Iterator<number>");
+        }
+
+        #[test]
+        fn pointers() {
+            let ty = <Box<[std::rc::Rc<str>]> as Exposed<TypeScript>>::AS;
+            assert_eq!(ty.to_string(), "Array<string>");
+            let ty = <std::sync::Arc<std::sync::Mutex<[u8; 4]>> as Exposed<TypeScript>>::AS;
+            assert_eq!(ty.to_string(), "Array<number>");
+        }
+
+        #[test]
+        fn list_likes() {
+            let ty = <std::collections::VecDeque<std::collections::BinaryHeap<u8>> as Exposed<
+                TypeScript,
+            >>::AS;
+            assert_eq!(ty.to_string(), "Array<Array<number>>");
+        }
+
+        #[test]
+        fn custom_types() {
+            let ty = <HashMap<String, Custom> as Exposed<TypeScript>>::AS;
+            assert_eq!(ty.to_string(), "Map<string, Custom>");
+        }
+
+        #[test]
+        fn classes() {
+            /// As `#[pyly::expose]` would implement it.
+            struct Paged<T>(T);
+
+            impl<T: Exposed<TypeScript>> Exposed<TypeScript> for Paged<T> {
+                const AS: Type<'static> = Type::Class {
+                    module: module_path!(),
+                    name: "Paged",
+                    args: &[T::AS],
+                };
+            }
+
+            let ty = <Vec<Paged<Option<bool>>> as Exposed<TypeScript>>::AS;
+            assert_eq!(ty.to_string(), "Array<Paged<boolean | null>>");
+        }
+
+        #[test]
+        fn paths() {
+            let ty = <Vec<&'static std::path::Path> as Exposed<TypeScript>>::AS;
+            assert_eq!(ty.to_string(), "Array<string>");
+            let ty = <Option<std::path::PathBuf> as Exposed<TypeScript>>::AS;
+            assert_eq!(ty.to_string(), "string | null");
+        }
+
+        #[test]
+        fn times() {
+            use std::time::{Duration, SystemTime};
+
+            let ty = <(Duration, SystemTime) as Exposed<TypeScript>>::AS;
+            assert_eq!(ty.to_string(), "[number, Date]");
+            assert_eq!(
+                <SystemTime as Exposed<TypeScript>>::AS,
+                Type::Interface {
+                    module: "",
+                    name: "Date"
+                }
+            );
+        }
+
+        #[test]
+        fn type_params() {
+            let ty = <Option<Vec<crate::TypeParam<"T">>> as Exposed<TypeScript>>::AS;
+            assert_eq!(ty.to_string(), "Array<T> | null");
+        }
+    }
+}
@@ -19,6 +19,7 @@ pub mod pyly;
 
 extern crate either;
 extern crate rustc_abi;
+extern crate rustc_ast;
 extern crate rustc_const_eval;
 extern crate rustc_data_structures;
 extern crate rustc_driver;
@@ -26,40 +27,85 @@ extern crate rustc_errors;
 extern crate rustc_hir;
 extern crate rustc_interface;
 extern crate rustc_middle;
+extern crate rustc_resolve;
 extern crate rustc_session;
 extern crate rustc_smir;
 extern crate rustc_span;
 extern crate rustc_type_ir;
 extern crate stable_mir;
 
-use std::{borrow::Cow, env, process::Command};
+use std::{borrow::Cow, collections::BTreeMap, env, fs, path::PathBuf, process::Command};
 
 use clap::Parser;
 
+use rustc_hir::def_id::LOCAL_CRATE;
 use rustc_middle::ty::TyCtxt;
 use rustc_plugin::{CrateFilter, RustcPlugin, RustcPluginArgs, Utf8Path};
+use rustc_span::Symbol;
 use serde::{Deserialize, Serialize};
 
 use crate::pyly::{
-    comptime::{self, exposed::PyLyCtx},
+    comptime::{
+        self,
+        exposed::{LangType, PyLyCtx},
+    },
+    docs::docs,
+    items, pyo3,
+    stubs::{
+        self,
+        python::{StubModule, StubStyle},
+        typescript::DtsModule,
+        Layout, Stubs,
+    },
     PyLy,
 };
 
-// This struct is the plugin provided to the rustc_plugin framework,
-// and it must be exported for use by the CLI/driver binaries.
+/// The `cargo pyly` plugin, run by the CLI and driver binaries.
 pub struct SveltePlugin;
 
-// To parse CLI arguments, we use Clap for this example. But that
-// detail is up to you.
 #[derive(Parser, Serialize, Deserialize)]
+#[command(about = "Generate Python (or TypeScript) type stubs for the items a crate exposes")]
 pub struct PyLyPluginArgs {
+    /// Directory to write the generated stubs into
+    /// (defaults to `pyly/` in Cargo's target directory).
     #[arg(short, long)]
-    allcaps: bool,
+    out_dir: Option<PathBuf>,
+
+    /// Which language to generate stubs for.
+    #[arg(long, value_enum, default_value_t)]
+    lang: Lang,
+
+    /// Only generate stubs for the workspace's own crates,
+    /// not their dependencies.
+    #[arg(long)]
+    workspace: bool,
+
+    /// Only generate stubs for this package (in the workspace).
+    #[arg(short, long)]
+    package: Option<String>,
+
+    /// Declare structs and enums that serde serializes as the JSON it writes
+    /// (`TypedDict`s and literals), rather than as classes.
+    #[arg(long)]
+    serde: bool,
+
+    #[command(flatten)]
+    style: StubStyle,
 
     #[clap(last = true)]
     cargo_args: Vec<String>,
 }
 
+#[derive(Debug, Default, Clone, Copy, clap::ValueEnum, Serialize, Deserialize)]
+pub enum Lang {
+    /// Python stubs (`.pyi`)
+    #[default]
+    Python,
+    /// TypeScript declarations (`.d.ts`)
+    #[value(name = "typescript")]
+    TypeScript,
+}
+
 impl RustcPlugin for SveltePlugin {
     type Args = PyLyPluginArgs;
 
@@ -71,12 +117,26 @@ impl RustcPlugin for SveltePlugin {
         "pyly-driver".into()
     }
 
-    // In the CLI, we ask Clap to parse arguments and also specify a CrateFilter.
-    // If one of the CLI arguments was a specific file to analyze, then you
-    // could provide a different filter.
-    fn args(&self, _target_dir: &Utf8Path) -> RustcPluginArgs<Self::Args> {
-        let args = PyLyPluginArgs::parse_from(env::args().skip(1));
-        let filter = CrateFilter::AllCrates;
+    // `--workspace` and `--package` leave out the dependencies' crates.
+    fn args(&self, target_dir: &Utf8Path) -> RustcPluginArgs<Self::Args> {
+        let mut args = PyLyPluginArgs::parse_from(env::args().skip(1));
+
+        // The driver runs from elsewhere, so it needs an absolute path.
+        let out_dir = match args.out_dir.take() {
+            Some(dir) => std::path::absolute(dir).expect("current directory exists"),
+            None => target_dir
+                .parent()
+                .unwrap_or(target_dir)
+                .join("pyly")
+                .into_std_path_buf(),
+        };
+        args.out_dir = Some(out_dir);
+
+        let filter = match args.workspace || args.package.is_some() {
+            true => CrateFilter::OnlyWorkspace,
+            false => CrateFilter::AllCrates,
+        };
+
         RustcPluginArgs { args, filter }
     }
 
@@ -90,42 +150,226 @@ impl RustcPlugin for SveltePlugin {
     fn run(
         self,
         compiler_args: Vec<String>,
-        _plugin_args: Self::Args,
+        plugin_args: Self::Args,
     ) -> rustc_interface::interface::Result<()> {
-        let mut callbacks = PyLyCallback {};
+        let mut callbacks = PyLyCallback { args: plugin_args };
         rustc_driver::run_compiler(&compiler_args, &mut callbacks);
         Ok(())
     }
 }
 
-struct PyLyCallback {}
+struct PyLyCallback {
+    args: PyLyPluginArgs,
+}
 
 impl rustc_driver::Callbacks for PyLyCallback {
-    // At the top-level, the Rustc API uses an event-based interface for
-    // accessing the compiler at different stages of compilation. In this callback,
-    // all the type-checking has completed.
+    // Intra-doc links are only resolved for rustdoc, unless asked for:
+    // we need them to link exposed items' docs to one another.
+    fn config(&mut self, config: &mut rustc_interface::interface::Config) {
+        config.opts.resolve_doc_links = rustc_session::config::ResolveDocLinks::All;
+    }
+
+    // Cargo only re-runs the driver on crates it thinks are stale,
+    // so record our arguments (e.g. `--out-dir`) and the driver itself
+    // as dependencies of the crate: changing either regenerates the stubs.
+    fn after_expansion(
+        &mut self,
+        _compiler: &rustc_interface::interface::Compiler,
+        tcx: TyCtxt<'_>,
+    ) -> rustc_driver::Compilation {
+        // Set by rustc_plugin (as `rustc_plugin::plugin::PLUGIN_ARGS`).
+        const PLUGIN_ARGS: &str = "PLUGIN_ARGS";
+
+        if let Ok(args) = env::var(PLUGIN_ARGS) {
+            tcx.sess
+                .psess
+                .env_depinfo
+                .lock()
+                .insert((Symbol::intern(PLUGIN_ARGS), Some(Symbol::intern(&args))));
+        }
+
+        // The crate's own manifest, too, for its root package.
+        let files = env::current_exe().ok().into_iter().chain(manifest());
+        for file in files {
+            tcx.sess
+                .psess
+                .file_depinfo
+                .lock()
+                .insert(Symbol::intern(&file.to_string_lossy()));
+        }
+
+        rustc_driver::Compilation::Continue
+    }
+
+    // Once everything is type-checked, generate the crate's stubs.
     fn after_analysis(
         &mut self,
         _compiler: &rustc_interface::interface::Compiler,
         tcx: TyCtxt<'_>,
     ) -> rustc_driver::Compilation {
-        let pyly = PyLy::new(tcx).expect("PyLy library present");
+        // Set by Cargo for each crate it compiles.
+        const CARGO_PKG_NAME: &str = "CARGO_PKG_NAME";
 
-        let py_ctx = &mut PyLyCtx::new();
-        tcx.all_impls(pyly.traits.exposed)
-            .filter(|did| did.krate != pyly.krate)
-            .for_each(|impl_| {
-                let ty = comptime::exposed::AS(tcx, &pyly, py_ctx, impl_);
-                println!("{}", ty.as_str());
-            });
+        if let Some(package) = &self.args.package {
+            if env::var(CARGO_PKG_NAME).ok().as_ref() != Some(package) {
+                return rustc_driver::Compilation::Continue;
+            }
+        }
+
+        // Crates which don't use PyLy have nothing to expose.
+        let pyly = match PyLy::new(tcx) {
+            Ok(Some(pyly)) => pyly,
+            Ok(None) => return rustc_driver::Compilation::Continue,
+            Err(err) => {
+                err.emit(tcx);
+                return rustc_driver::Compilation::Stop;
+            }
+        };
+
+        pyo3::check(tcx);
 
-        // let impls_exposed =
-        // exposed_trait.skip_binder();
-        //     tcx.def_path_table().def_key(index).enumerated_keys_and_path_hashes().filter(|(a, b, c)| {
-        //         b.
-        //     })
-        // let hir = tcx.def_path_str(def_kind, def_id)
+        let krate = tcx.crate_name(LOCAL_CRATE).to_ident_string();
+        let layout = Layout::new(&krate, root_package().as_deref());
+
+        match self.args.lang {
+            Lang::Python => self.generate(tcx, &pyly, &layout, |module| {
+                StubModule::new(module, layout.clone(), self.args.style)
+            }),
+            Lang::TypeScript => self.generate(tcx, &pyly, &layout, |module| {
+                DtsModule::new(module, layout.clone())
+            }),
+        }
 
         rustc_driver::Compilation::Continue
     }
 }
+
+impl PyLyCallback {
+    /// Declare everything this crate exposes in the stubs for its module
+    /// (made with `new`, and laid out by `layout`), then write them out.
+    ///
+    /// Only modules with something declared (or re-exported) in them get stubs,
+    /// and those with others inside them are written as packages.
+    fn generate<S: Stubs>(
+        &self,
+        tcx: TyCtxt<'_>,
+        pyly: &PyLy,
+        layout: &Layout,
+        new: impl Fn(&str) -> S,
+    ) {
+        let py_ctx = &mut PyLyCtx::new();
+
+        let mut modules = BTreeMap::<String, S>::new();
+
+        items::adts(tcx).for_each(|did| {
+            let stubs = modules
+                .entry(layout.module(&items::module(tcx, did)))
+                .or_insert_with_key(|module| new(module));
+            match self.args.serde
+                && crate::pyly::serde::is_serialized(tcx, did)
+                && !pyo3::is_class(tcx, did)
+            {
+                true => crate::pyly::serde::declare(tcx, pyly, py_ctx, stubs, did),
+                false => items::declare(tcx, pyly, py_ctx, stubs, did),
+            }
+        });
+        items::traits(tcx).for_each(|did| {
+            let stubs = modules
+                .entry(layout.module(&items::module(tcx, did)))
+                .or_insert_with_key(|module| new(module));
+            items::declare_trait(tcx, pyly, py_ctx, stubs, did);
+        });
+        items::fns(tcx).for_each(|did| {
+            let stubs = modules
+                .entry(layout.module(&items::module(tcx, did)))
+                .or_insert_with_key(|module| new(module));
+            items::declare_fn(tcx, pyly, py_ctx, stubs, did);
+        });
+        items::consts(tcx).for_each(|did| {
+            let stubs = modules
+                .entry(layout.module(&items::module(tcx, did)))
+                .or_insert_with_key(|module| new(module));
+            items::declare_const(tcx, pyly, py_ctx, stubs, did);
+        });
+        items::reexports(tcx).for_each(|(module, did, alias)| {
+            let (module, declared) = (layout.module(&module), items::module(tcx, did));
+            if module == layout.module(&declared) {
+                return;
+            }
+
+            let stubs = modules
+                .entry(module)
+                .or_insert_with_key(|module| new(module));
+            stubs.reexport(&declared, &items::name(tcx, did), &alias);
+        });
+
+        let aliases = items::aliases::<S::Type>(tcx, pyly).collect::<Vec<_>>();
+        aliases.into_iter().for_each(|(impl_, adt)| {
+            let name = tcx.item_name(adt).to_ident_string();
+
+            let ty = match comptime::exposed::AS::<S::Type>(tcx, pyly, py_ctx, impl_) {
+                Ok(ty) => ty,
+                Err(err) => {
+                    err.emit(tcx);
+                    return;
+                }
+            };
+
+            // Only there for other types to refer to this one.
+            if ty.custom_name() == Some(&name) {
+                return;
+            }
+
+            let stubs = modules
+                .entry(layout.module(&items::module(tcx, adt)))
+                .or_insert_with_key(|module| new(module));
+            let mut generics = items::generics(tcx, impl_);
+            for parameter in ty.parameters() {
+                if !generics.iter().any(|generic| generic == parameter) {
+                    generics.push(parameter.to_string());
+                }
+            }
+
+            let ty = stubs.annotation(ty);
+            let doc = docs(tcx, pyly, stubs, adt);
+            stubs.alias(&name, doc, &generics, ty);
+        });
+
+        let names = modules.keys().cloned().collect::<Vec<_>>();
+
+        let out_dir = self.args.out_dir.as_deref().expect("set by the CLI");
+        for (module, stubs) in modules {
+            let package = names.iter().any(|other| {
+                other
+                    .strip_prefix(&module)
+                    .is_some_and(|rest| rest.starts_with('.'))
+            });
+            if let Err(err) = stubs::write(out_dir, &module, package, stubs) {
+                err.emit(tcx);
+            }
+        }
+    }
+}
+
+/// The crate's own `Cargo.toml`, if it's being built by Cargo.
+fn manifest() -> Option<PathBuf> {
+    let dir = env::var_os("CARGO_MANIFEST_DIR")?;
+    Some(PathBuf::from(dir).join("Cargo.toml"))
+}
+
+/// The root package of the crate's stubs, if it's renamed in its `Cargo.toml`:
+/// ```toml
+/// [package.metadata.pyly]
+/// module = "mypackage.native"
+/// ```
+fn root_package() -> Option<String> {
+    let manifest = fs::read_to_string(manifest()?).ok()?;
+    let manifest = manifest.parse::<toml::Table>().ok()?;
+
+    let module = manifest
+        .get("package")?
+        .get("metadata")?
+        .get("pyly")?
+        .get("module")?;
+    module.as_str().map(String::from)
+}
@@ -3,72 +3,42 @@
 //!
 
 pub mod exposed {
-    use std::{
-        marker::DiscriminantKind,
-        mem::{self, MaybeUninit},
-        ptr::{self, NonNull},
-    };
+    use std::ptr::NonNull;
 
-    use pyly_lib::python;
+    use pyly_lib::{python, typescript};
     use rustc_const_eval::{
         const_eval::{mk_eval_cx_for_const_val, CompileTimeMachine},
-        interpret::{InterpCx, InterpResult, OpTy, Projectable},
+        interpret::{format_interp_error, interp_ok, InterpCx, InterpResult, OpTy, Projectable},
     };
     use rustc_hir::{def::Namespace, def_id::DefId};
-    use rustc_middle::ty::{TyCtxt, TyKind, TypingEnv};
-    use rustc_span::Ident;
+    use rustc_middle::{
+        err_unsup_format,
+        mir::ConstValue,
+        ty::{
+            self, fold::BottomUpFolder, GenericArgs, Instance, Ty, TyCtxt, TyKind, TypeFoldable,
+            TypingEnv, ValTree,
+        },
+    };
+    use rustc_span::{Ident, Span};
 
-    use crate::pyly::PyLy;
+    use crate::pyly::{error::PyLyError, PyLy};
 
     type Icx<'tcx> = InterpCx<'tcx, CompileTimeMachine<'tcx>>;
 
-    fn read_disc_then_first_field<'a, D: DiscriminantKind>(
-        icx: &Icx<'a>,
-        op: OpTy<'a>,
-    ) -> (D::Discriminant, Option<OpTy<'a>>)
-    where
-        D::Discriminant: TryFrom<usize>,
-        <D::Discriminant as TryFrom<usize>>::Error: std::fmt::Debug,
-    {
-        let variant = icx.read_discriminant(&op).unwrap();
-        let down = icx.project_downcast(&op, variant).unwrap();
-        let variant: D::Discriminant = TryFrom::try_from(variant.as_usize()).unwrap();
-
-        if down.layout.fields.count() == 0 {
-            return (variant, None);
-        }
-
-        let offset = down.layout.fields.offset(0);
-        let layout = down.layout.field(icx, 0);
-        let field = op.offset(offset, layout, icx).unwrap();
-        (variant, Some(field))
-    }
-
-    fn read_field<'a>(icx: &Icx<'a>, op: &OpTy<'a>, i: usize) -> InterpResult<'a, OpTy<'a>> {
-        let offset = op.layout.fields.offset(i);
-        let layout = op.layout.field(icx, i);
-        op.offset(offset, layout, icx)
-    }
-
-    #[derive(Debug, PartialEq, Eq)]
+    #[derive(Debug)]
     pub enum StoredType {
         Single(NonNull<python::Type<'static>>),
         Dual(NonNull<[python::Type<'static>; 2]>),
         Multiple(NonNull<[python::Type<'static>]>),
+        Str(NonNull<str>),
+        Literals(NonNull<[python::Literal<'static>]>),
+        TsSingle(NonNull<typescript::Type<'static>>),
+        TsDual(NonNull<[typescript::Type<'static>; 2]>),
+        TsMultiple(NonNull<[typescript::Type<'static>]>),
     }
 
-    impl StoredType {
-        fn extract<Ty: FromStoredType + ?Sized>(this: &StoredType) -> &'static Ty {
-            Ty::from_stored_type(this)
-        }
-    }
-
-    trait IntoStoredType {
+    pub trait IntoStoredType {
         const FN: fn(NonNull<Self>) -> StoredType;
-
-        fn into_stored_type(this: Box<Self>) -> StoredType {
-            Self::FN(Box::into_non_null(this))
-        }
     }
 
     impl IntoStoredType for python::Type<'static> {
@@ -83,58 +53,143 @@ pub mod exposed {
         const FN: fn(NonNull<Self>) -> StoredType = StoredType::Multiple;
     }
 
-    trait FromStoredType: IntoStoredType {
-        fn from_stored_type(ty: &StoredType) -> &'static Self;
+    impl IntoStoredType for str {
+        const FN: fn(NonNull<Self>) -> StoredType = StoredType::Str;
     }
 
-    macro_rules! impl_from_stored_type {
-        ($ty: ty, $var: ident) => {
-            impl FromStoredType for $ty {
-                fn from_stored_type(ty: &StoredType) -> &'static Self {
-                    match ty {
-                        StoredType::$var(ptr) => unsafe { ptr.as_ptr().as_ref().unwrap() },
-                        _ => unimplemented!(),
-                    }
-                }
-            }
-        };
+    impl IntoStoredType for [python::Literal<'static>] {
+        const FN: fn(NonNull<Self>) -> StoredType = StoredType::Literals;
     }
 
-    impl_from_stored_type!(python::Type<'static>, Single);
-    impl_from_stored_type!([python::Type<'static>; 2], Dual);
-    impl_from_stored_type!([python::Type<'static>], Multiple);
+    impl IntoStoredType for typescript::Type<'static> {
+        const FN: fn(NonNull<Self>) -> StoredType = StoredType::TsSingle;
+    }
 
-    trait PossibleRef {
-        type Inner<T>: Sized
-        where
-            T: 'static;
+    impl IntoStoredType for [typescript::Type<'static>; 2] {
+        const FN: fn(NonNull<Self>) -> StoredType = StoredType::TsDual;
+    }
 
-        fn intern<T: FromStoredType + 'static>(ctx: &mut PyLyCtx, type_: T) -> Self::Inner<T>;
+    impl IntoStoredType for [typescript::Type<'static>] {
+        const FN: fn(NonNull<Self>) -> StoredType = StoredType::TsMultiple;
     }
 
-    struct ByRef;
+    /// A [pyly_lib::Language]'s type, which can be read back out of
+    /// an evaluated [pyly_lib::Exposed::AS].
+    pub trait LangType: IntoStoredType + Default + 'static {
+        /// The language itself (e.g. [pyly_lib::Python]).
+        fn language(pyly: &PyLy) -> DefId;
 
-    impl PossibleRef for ByRef {
-        type Inner<T>
-            = &'static T
-        where
-            T: 'static;
+        fn read<'tcx>(
+            ctx: &mut PyLyCtx,
+            icx: &Icx<'tcx>,
+            op: &OpTy<'tcx>,
+        ) -> InterpResult<'tcx, Self>;
+
+        /// The name of the Rust type this refers to, if it's one
+        /// exposed by name (e.g. [python::Type::Custom]).
+        fn custom_name(&self) -> Option<&str>;
+
+        /// What this may raise (or throw) instead, if it's a [Result]
+        /// (e.g. [python::Type::Raises]).
+        fn raises(&self) -> Option<&Self>;
 
-        fn intern<T: FromStoredType + 'static>(ctx: &mut PyLyCtx, type_: T) -> Self::Inner<T> {
-            ctx.intern_type(Box::new(type_))
+        /// Any type parameters this refers to, which an alias of it
+        /// has to declare itself (besides those of its impl).
+        fn parameters(&self) -> Vec<&str>;
+    }
+
+    impl LangType for python::Type<'static> {
+        fn language(pyly: &PyLy) -> DefId {
+            pyly.py.python
+        }
+
+        fn read<'tcx>(
+            ctx: &mut PyLyCtx,
+            icx: &Icx<'tcx>,
+            op: &OpTy<'tcx>,
+        ) -> InterpResult<'tcx, Self> {
+            ctx.read_py_type(icx, op)
+        }
+
+        fn custom_name(&self) -> Option<&str> {
+            match self {
+                python::Type::Custom { name, .. } | python::Type::Class { name, .. } => Some(name),
+                _ => None,
+            }
+        }
+
+        fn raises(&self) -> Option<&Self> {
+            match self {
+                python::Type::Raises { raises, .. } => Some(raises),
+                _ => None,
+            }
+        }
+
+        /// None: the annotation of a [python::Type::TypeVar] declares it already.
+        fn parameters(&self) -> Vec<&str> {
+            Vec::new()
         }
     }
 
-    struct ByValue;
+    impl LangType for typescript::Type<'static> {
+        fn language(pyly: &PyLy) -> DefId {
+            pyly.ts.typescript
+        }
 
-    impl PossibleRef for ByValue {
-        type Inner<T>
-            = T
-        where
-            T: 'static;
+        fn read<'tcx>(
+            ctx: &mut PyLyCtx,
+            icx: &Icx<'tcx>,
+            op: &OpTy<'tcx>,
+        ) -> InterpResult<'tcx, Self> {
+            ctx.read_ts_type(icx, op)
+        }
+
+        fn custom_name(&self) -> Option<&str> {
+            match self {
+                typescript::Type::Interface { name, .. } | typescript::Type::Class { name, .. } => {
+                    Some(name)
+                }
+                _ => None,
+            }
+        }
 
-        fn intern<T: FromStoredType + 'static>(_: &mut PyLyCtx, type_: T) -> Self::Inner<T> {
-            type_
+        fn raises(&self) -> Option<&Self> {
+            match self {
+                typescript::Type::Throws { throws, .. } => Some(throws),
+                _ => None,
+            }
+        }
+
+        fn parameters(&self) -> Vec<&str> {
+            use typescript::Type;
+
+            let mut parameters = Vec::new();
+            let mut todo = vec![self];
+            while let Some(ty) = todo.pop() {
+                match ty {
+                    Type::Parameter("Self") => {}
+                    Type::Parameter(name) => {
+                        if !parameters.contains(name) {
+                            parameters.push(*name);
+                        }
+                    }
+                    Type::Array(item) | Type::Set(item) | Type::Iterator(item) => todo.push(item),
+                    Type::Record(items) | Type::Map(items) => todo.extend(items.iter().rev()),
+                    Type::Tuple(items) | Type::Union(items) | Type::Class { args: items, .. } => {
+                        todo.extend(items.iter().rev())
+                    }
+                    // Only `returns` is annotated: `throws` is just documented.
+                    Type::Throws { returns, .. } => todo.push(returns),
+                    Type::Null
+                    | Type::Boolean
+                    | Type::Number
+                    | Type::BigInt
+                    | Type::String
+                    | Type::Unknown
+                    | Type::Interface { .. } => {}
+                }
+            }
+            parameters
         }
     }
 
@@ -145,6 +200,11 @@ pub mod exposed {
                     StoredType::Single(ptr) => drop(Box::from_non_null(*ptr)),
                     StoredType::Dual(ptr) => drop(Box::from_non_null(*ptr)),
                     StoredType::Multiple(ptr) => drop(Box::from_non_null(*ptr)),
+                    StoredType::Str(ptr) => drop(Box::from_non_null(*ptr)),
+                    StoredType::Literals(ptr) => drop(Box::from_non_null(*ptr)),
+                    StoredType::TsSingle(ptr) => drop(Box::from_non_null(*ptr)),
+                    StoredType::TsDual(ptr) => drop(Box::from_non_null(*ptr)),
+                    StoredType::TsMultiple(ptr) => drop(Box::from_non_null(*ptr)),
                 };
             }
         }
@@ -169,223 +229,354 @@ pub mod exposed {
     }
 
     impl PyLyCtx {
-        fn intern_type<Ty: FromStoredType + ?Sized>(&mut self, ty: Box<Ty>) -> &'static Ty {
-            let desired = Ty::into_stored_type(ty);
-            if let Some(ty) = self.stored_types.iter().find(|&ty| &desired == ty) {
-                return StoredType::extract(ty);
-            }
+        /// Keep `ty` alive for as long as this context is.
+        fn intern_type<Ty: IntoStoredType + ?Sized>(&mut self, ty: Box<Ty>) -> &'static Ty {
+            let ptr = Box::into_non_null(ty);
+            self.stored_types.push(Ty::FN(ptr));
 
-            self.stored_types.push(desired);
-            Ty::from_stored_type(self.stored_types.last().unwrap())
+            // Only freed when the context is dropped.
+            unsafe { ptr.as_ref() }
+        }
+
+        /// Read (and intern) the string behind a `&str`.
+        fn read_str<'tcx>(
+            &mut self,
+            icx: &Icx<'tcx>,
+            op: &OpTy<'tcx>,
+        ) -> InterpResult<'tcx, &'static str> {
+            let place = icx.deref_pointer(op)?;
+            let str = icx.read_str(&place)?;
+            interp_ok(self.intern_type(str.into()))
+        }
+
+        /// Read (and intern) the type behind a `&T`.
+        fn read_ref<'tcx, T: LangType>(
+            &mut self,
+            icx: &Icx<'tcx>,
+            op: &OpTy<'tcx>,
+        ) -> InterpResult<'tcx, &'static T> {
+            let place = icx.deref_pointer(op)?;
+            let ty = T::read(self, icx, &place.into())?;
+            interp_ok(self.intern_type(Box::new(ty)))
+        }
+
+        /// Read each type behind a `&[T]` or `&[T; N]`.
+        fn read_elems<'tcx, T: LangType>(
+            &mut self,
+            icx: &Icx<'tcx>,
+            op: &OpTy<'tcx>,
+        ) -> InterpResult<'tcx, Vec<T>> {
+            let place = icx.deref_pointer(op)?;
+            let len = place.len(icx)?;
+
+            (0..len)
+                .map(|i| {
+                    let elem = icx.project_index(&place, i)?;
+                    T::read(self, icx, &elem.into())
+                })
+                .collect()
+        }
+
+        /// Read (and intern) the types behind a `&[T]`.
+        fn read_slice<'tcx, T: LangType>(
+            &mut self,
+            icx: &Icx<'tcx>,
+            op: &OpTy<'tcx>,
+        ) -> InterpResult<'tcx, &'static [T]>
+        where
+            [T]: IntoStoredType,
+        {
+            let elems = self.read_elems(icx, op)?;
+            interp_ok(self.intern_type(elems.into_boxed_slice()))
+        }
+
+        /// Read (and intern) the pair of types behind a `&[T; 2]`.
+        fn read_pair<'tcx, T: LangType>(
+            &mut self,
+            icx: &Icx<'tcx>,
+            op: &OpTy<'tcx>,
+        ) -> InterpResult<'tcx, &'static [T; 2]>
+        where
+            [T; 2]: IntoStoredType,
+        {
+            let Ok(pair) = <[T; 2]>::try_from(self.read_elems(icx, op)?) else {
+                return Err(err_unsup_format!("expected a pair of types")).into();
+            };
+
+            interp_ok(self.intern_type(Box::new(pair)))
         }
 
         fn read_py_in_built<'tcx>(
             &mut self,
-            tcx: TyCtxt<'tcx>,
             icx: &Icx<'tcx>,
-            pyly: &PyLy,
-            op: OpTy<'tcx>,
-        ) -> python::InBuilt<'static> {
-            let mut uninit = MaybeUninit::<python::InBuilt<'static>>::uninit();
-            let (var, op) = read_disc_then_first_field::<python::InBuilt<'static>>(icx, op);
-
-            // TODO: Refactor this such that only 0..=8 use
-            //       the discriminant trick.
-
-            // 1. Write the discriminant.
-            // SAFETY: We are aligned to a usize
-            //         by the layout of InBuilt<'_>.
-            unsafe {
-                (uninit.as_mut_ptr() as *mut usize).write(var as usize);
-            }
+            op: &OpTy<'tcx>,
+        ) -> InterpResult<'tcx, python::InBuilt<'static>> {
+            use python::InBuilt;
 
-            match (var, op) {
-                // None, Ellipses, Int, Float, Complex, Bool, Str, Bytes, ByteArray
-                (0..=8, None) => (), // Do nothing...
-
-                // Tuple
-                (9, Some(ref op)) => {
-                    // Get the individual layout of one.
-                    let typing_env = TypingEnv::fully_monomorphized();
-                    let layout =
-                        if let rustc_type_ir::TyKind::Ref(_, ty, _) = op.layout.ty.kind() {
-                            if let rustc_type_ir::TyKind::Slice(ty) = ty.kind() {
-                                tcx.layout_of(typing_env.as_query_input(*ty))
-                            } else {
-                                unreachable!()
-                            }
-                        } else {
-                            unreachable!("Should only be a reference type.")
-                        }
-                        .unwrap();
-
-                    let ptr = read_field(icx, op, 0)
-                        .and_then(|ref op| icx.read_pointer(op))
-                        .unwrap();
-
-                    let len = read_field(icx, op, 1)
-                        .and_then(|ref op| icx.read_target_usize(op))
-                        .unwrap();
-
-                    let tys = (0..len)
-                        .map(|i| {
-                            let offset = layout.size.checked_mul(i, &tcx).unwrap();
-                            let op = icx
-                                .ptr_to_mplace(ptr.wrapping_offset(offset, &tcx), layout)
-                                .to_op(icx)
-                                .unwrap();
-
-                            self.read_py_type(tcx, icx, pyly, op, ByValue)
-                        })
-                        .collect::<Vec<_>>()
-                        .into_boxed_slice();
-
-                    let inner = self.intern_type(tys);
-                    let ptr = unsafe {
-                        let ptr = uninit
-                            .as_mut_ptr()
-                            .byte_add(mem::offset_of!(python::InBuilt, Tuple.0));
-
-                        ptr::from_raw_parts_mut::<&[python::Type<'static>]>(ptr, ())
-                    };
-                    unsafe { ptr.write(inner) };
+            let variant = icx.read_discriminant(op)?;
+            let down = icx.project_downcast(op, variant)?;
+            let field = |i| icx.project_field(&down, i);
+
+            // Variant in source-order, so this is fine:
+            let in_built = match variant.as_usize() {
+                0 => InBuilt::None,
+                1 => InBuilt::Ellipses,
+                2 => InBuilt::Int,
+                3 => InBuilt::Float,
+                4 => InBuilt::Complex,
+                5 => InBuilt::Bool,
+                6 => InBuilt::Str,
+                7 => InBuilt::Bytes,
+                8 => InBuilt::ByteArray,
+                9 => InBuilt::Tuple(self.read_slice(icx, &field(0)?)?),
+                10 => InBuilt::List(self.read_ref(icx, &field(0)?)?),
+                11 => InBuilt::Set(self.read_ref(icx, &field(0)?)?),
+                12 => InBuilt::Dict(self.read_pair(icx, &field(0)?)?),
+                other => {
+                    return Err(err_unsup_format!(
+                        "unknown `python::InBuilt` variant {other}"
+                    ))
+                    .into()
                 }
+            };
 
-                // List, Set, Dict
-                (10..=12, Some(op)) => {
-                    let ptr = icx.read_pointer(&op).unwrap();
+            interp_ok(in_built)
+        }
 
-                    let typing_env = TypingEnv::fully_monomorphized();
-                    let layout = match op.layout.ty.kind() {
-                        rustc_type_ir::TyKind::Ref(_, ty, _) => {
-                            tcx.layout_of(typing_env.as_query_input(*ty))
-                        }
-                        _ => unreachable!("Should only be a reference type."),
-                    }
-                    .unwrap();
-
-                    // Read the pointer:
-                    // &Type<'_> | &[Type<'_>; 2]
-                    let op = icx.ptr_to_mplace(ptr, layout).to_op(icx).unwrap();
-                    match op.layout.ty.kind() {
-                        // Dict => [Type<'_>; 2]
-                        TyKind::Array(ty, len) => {
-                            let len = len.try_to_target_usize(tcx).unwrap();
-                            let layout = tcx.layout_of(typing_env.as_query_input(*ty)).unwrap();
-
-                            assert_eq!(len, 2);
-                            assert_eq!(ty.ty_adt_def().map(|a| a.did()), Some(pyly.py.type_));
-
-                            let inner: [_; 2] = (0..len)
-                                .map(|i| {
-                                    let offset = layout.size.checked_mul(i, &tcx).unwrap();
-                                    let op = icx
-                                        .ptr_to_mplace(ptr.wrapping_offset(offset, &tcx), layout)
-                                        .to_op(icx)
-                                        .unwrap();
-
-                                    self.read_py_type(tcx, icx, pyly, op, ByValue)
-                                })
-                                .collect::<Vec<_>>()
-                                .try_into()
-                                .unwrap();
-
-                            let inner = self.intern_type(Box::new(inner));
-                            let ptr = unsafe {
-                                uninit
-                                    .as_mut_ptr()
-                                    .byte_add(mem::offset_of!(python::InBuilt, Dict.0))
-                                    as *mut &'static [python::Type<'static>; 2]
-                            };
-
-                            unsafe {
-                                ptr.write(inner);
-                            }
-                        }
-                        TyKind::Adt(adt, _) => {
-                            assert_eq!(adt.did(), pyly.py.type_);
-
-                            let inner = self.read_py_type(tcx, icx, pyly, op, ByValue);
-                            let inner = self.intern_type(Box::new(inner));
-
-                            let ptr = unsafe {
-                                // Should be same offset with List, Set...
-                                assert_eq!(
-                                    mem::offset_of!(python::InBuilt, List.0),
-                                    mem::offset_of!(python::InBuilt, Set.0)
-                                );
-
-                                uninit
-                                    .as_mut_ptr()
-                                    .byte_add(mem::offset_of!(python::InBuilt, List.0))
-                                    as *mut &'static python::Type<'static>
-                            };
-
-                            unsafe {
-                                ptr.write(inner);
-                            }
-                        }
-                        _ => unreachable!(),
-                    }
+        fn read_py_typing<'tcx>(
+            &mut self,
+            icx: &Icx<'tcx>,
+            op: &OpTy<'tcx>,
+        ) -> InterpResult<'tcx, python::Typing<'static>> {
+            use python::Typing;
+
+            let variant = icx.read_discriminant(op)?;
+            let down = icx.project_downcast(op, variant)?;
+            let field = |i| icx.project_field(&down, i);
+
+            // Variant in source-order, so this is fine:
+            let typing = match variant.as_usize() {
+                0 => Typing::Iterator(self.read_ref(icx, &field(0)?)?),
+                1 => Typing::Any,
+                2 => Typing::Optional(self.read_ref(icx, &field(0)?)?),
+                3 => Typing::Literal(self.read_py_literals(icx, &field(0)?)?),
+                4 => Typing::Callable {
+                    params: self.read_slice(icx, &field(0)?)?,
+                    returns: self.read_ref(icx, &field(1)?)?,
+                },
+                other => {
+                    return Err(err_unsup_format!(
+                        "unknown `python::Typing` variant {other}"
+                    ))
+                    .into()
                 }
-                _ => unreachable!("No other variants!"),
-            }
+            };
 
-            unsafe { uninit.assume_init() }
+            interp_ok(typing)
         }
 
-        #[allow(unused)]
-        fn read_py_typing<'tcx>(
+        /// Read (and intern) the values behind a `&[python::Literal]`.
+        fn read_py_literals<'tcx>(
             &mut self,
-            tcx: TyCtxt<'tcx>,
             icx: &Icx<'tcx>,
-            pyly: &PyLy,
-            op: OpTy<'tcx>,
-        ) -> python::Typing<'static> {
-            todo!("{op:#?}")
+            op: &OpTy<'tcx>,
+        ) -> InterpResult<'tcx, &'static [python::Literal<'static>]> {
+            use python::Literal;
+
+            let place = icx.deref_pointer(op)?;
+            let len = place.len(icx)?;
+
+            let literals = (0..len)
+                .map(|i| {
+                    let elem = icx.project_index(&place, i)?;
+                    let variant = icx.read_discriminant(&elem)?;
+                    let down = icx.project_downcast(&elem, variant)?;
+                    let value = icx.project_field(&down, 0)?;
+
+                    // Variant in source-order, so this is fine:
+                    let literal = match variant.as_usize() {
+                        0 => Literal::Int(icx.read_scalar(&value)?.to_int(value.layout.size)?),
+                        1 => Literal::Str(self.read_str(icx, &value.into())?),
+                        2 => Literal::Bool(icx.read_scalar(&value)?.to_bool()?),
+                        other => {
+                            return Err(err_unsup_format!(
+                                "unknown `python::Literal` variant {other}"
+                            ))
+                            .into()
+                        }
+                    };
+
+                    interp_ok(literal)
+                })
+                .collect::<InterpResult<'tcx, Vec<_>>>()?;
+
+            interp_ok(self.intern_type(literals.into_boxed_slice()))
         }
 
-        fn read_py_type<'tcx, P: PossibleRef>(
+        fn read_py_type<'tcx>(
             &mut self,
-            tcx: TyCtxt<'tcx>,
             icx: &Icx<'tcx>,
-            pyly: &PyLy,
-            op: OpTy<'tcx>,
-            _: P,
-        ) -> P::Inner<python::Type<'static>> {
-            // 1. Read discriminant.
-            let (var, field) = read_disc_then_first_field::<python::Type>(icx, op);
+            op: &OpTy<'tcx>,
+        ) -> InterpResult<'tcx, python::Type<'static>> {
+            use python::Type;
+
+            let variant = icx.read_discriminant(op)?;
+            let down = icx.project_downcast(op, variant)?;
+            let field = |i| icx.project_field(&down, i);
 
             // Variant in source-order, so this is fine:
-            let type_ = match (var, field) {
-                // BuiltIn
-                (0, Some(op)) => {
-                    let in_built = self.read_py_in_built(tcx, icx, pyly, op);
-                    python::Type::InBuilt(in_built)
+            let type_ = match variant.as_usize() {
+                0 => Type::InBuilt(self.read_py_in_built(icx, &field(0)?)?),
+                1 => Type::Typing(self.read_py_typing(icx, &field(0)?)?),
+                2 => Type::Union(self.read_slice(icx, &field(0)?)?),
+                3 => Type::TypeVar(self.read_str(icx, &field(0)?)?),
+                // Raises
+                4 => Type::Raises {
+                    returns: self.read_ref(icx, &field(0)?)?,
+                    raises: self.read_ref(icx, &field(1)?)?,
+                },
+                // Custom
+                5 => Type::Custom {
+                    module: self.read_str(icx, &field(0)?)?,
+                    name: self.read_str(icx, &field(1)?)?,
+                },
+                // Class
+                6 => Type::Class {
+                    module: self.read_str(icx, &field(0)?)?,
+                    name: self.read_str(icx, &field(1)?)?,
+                    args: self.read_slice(icx, &field(2)?)?,
+                },
+                other => {
+                    return Err(err_unsup_format!("unknown `python::Type` variant {other}")).into()
                 }
-                // Typing
-                (1, Some(op)) => {
-                    let typing = self.read_py_typing(tcx, icx, pyly, op);
-                    python::Type::Typing(typing)
+            };
+
+            interp_ok(type_)
+        }
+
+        fn read_ts_type<'tcx>(
+            &mut self,
+            icx: &Icx<'tcx>,
+            op: &OpTy<'tcx>,
+        ) -> InterpResult<'tcx, typescript::Type<'static>> {
+            use typescript::Type;
+
+            let variant = icx.read_discriminant(op)?;
+            let down = icx.project_downcast(op, variant)?;
+            let field = |i| icx.project_field(&down, i);
+
+            // Variant in source-order, so this is fine:
+            let type_ = match variant.as_usize() {
+                0 => Type::Null,
+                1 => Type::Boolean,
+                2 => Type::Number,
+                3 => Type::BigInt,
+                4 => Type::String,
+                5 => Type::Unknown,
+                6 => Type::Array(self.read_ref(icx, &field(0)?)?),
+                7 => Type::Tuple(self.read_slice(icx, &field(0)?)?),
+                8 => Type::Record(self.read_pair(icx, &field(0)?)?),
+                9 => Type::Set(self.read_ref(icx, &field(0)?)?),
+                10 => Type::Map(self.read_pair(icx, &field(0)?)?),
+                11 => Type::Iterator(self.read_ref(icx, &field(0)?)?),
+                12 => Type::Union(self.read_slice(icx, &field(0)?)?),
+                13 => Type::Parameter(self.read_str(icx, &field(0)?)?),
+                // Throws
+                14 => Type::Throws {
+                    returns: self.read_ref(icx, &field(0)?)?,
+                    throws: self.read_ref(icx, &field(1)?)?,
+                },
+                // Interface
+                15 => Type::Interface {
+                    module: self.read_str(icx, &field(0)?)?,
+                    name: self.read_str(icx, &field(1)?)?,
+                },
+                // Class
+                16 => Type::Class {
+                    module: self.read_str(icx, &field(0)?)?,
+                    name: self.read_str(icx, &field(1)?)?,
+                    args: self.read_slice(icx, &field(2)?)?,
+                },
+                other => {
+                    return Err(err_unsup_format!(
+                        "unknown `typescript::Type` variant {other}"
+                    ))
+                    .into()
                 }
-                // Custom
-                (2, None) => python::Type::Custom,
+            };
+
+            interp_ok(type_)
+        }
 
-                // No other possibilities.
-                _ => unreachable!("Only 3 variants (for now!)"),
+        /// Read (and intern) an evaluated `AS` constant, of type `as_ty`.
+        fn read_as<'tcx, T: LangType>(
+            &mut self,
+            tcx: TyCtxt<'tcx>,
+            ty_env: TypingEnv<'tcx>,
+            span: Span,
+            item: impl FnOnce() -> String,
+            (as_val, as_ty): (ConstValue<'tcx>, Ty<'tcx>),
+        ) -> Result<&'static T, PyLyError> {
+            let Some((icx, op)) = mk_eval_cx_for_const_val(tcx.at(span), ty_env, as_val, as_ty)
+            else {
+                return Err(PyLyError::Read {
+                    span,
+                    item: item(),
+                    reason: "it has no value in memory".to_string(),
+                });
             };
 
-            P::intern(self, type_)
+            match T::read(self, &icx, &op).report_err() {
+                Ok(ty) => Ok(self.intern_type(Box::new(ty))),
+                Err(err) => Err(PyLyError::Read {
+                    span,
+                    item: item(),
+                    reason: format_interp_error(tcx.dcx(), err),
+                }),
+            }
         }
     }
 
+    /// Stand in for each generic type parameter `T` within `value`
+    /// with a `pyly_lib::TypeParam<"T">`, so it can be evaluated.
+    pub fn type_params<'tcx, V: TypeFoldable<TyCtxt<'tcx>>>(
+        tcx: TyCtxt<'tcx>,
+        pyly: &PyLy,
+        value: V,
+    ) -> V {
+        let type_param = tcx.adt_def(pyly.type_param);
+
+        let value = value.fold_with(&mut BottomUpFolder {
+            tcx,
+            ty_op: |ty: Ty<'tcx>| match ty.kind() {
+                TyKind::Param(param) => {
+                    let name = param.name.as_str().as_bytes();
+                    let name = ty::Const::new_value(
+                        tcx,
+                        ValTree::from_raw_bytes(tcx, name),
+                        Ty::new_static_str(tcx),
+                    );
+
+                    Ty::new_adt(tcx, type_param, tcx.mk_args(&[name.into()]))
+                }
+                _ => ty,
+            },
+            lt_op: |lt| lt,
+            ct_op: |ct| ct,
+        });
+
+        tcx.erase_regions(value)
+    }
+
+    /// Evaluate the `AS` of an `impl pyly_lib::Exposed<L> for _`,
+    /// standing in for any of its generic type parameters (see [type_params]).
     #[allow(non_snake_case)]
-    pub fn AS<'a>(
+    pub fn AS<'a, T: LangType>(
         tcx: TyCtxt,
         pyly: &PyLy,
         ctx: &'a mut PyLyCtx,
         impl_: DefId,
-    ) -> &'a python::Type<'a> {
+    ) -> Result<&'a T, PyLyError> {
         let assoc_items = tcx.associated_items(impl_);
         let as_const = assoc_items
             .find_by_name_and_namespace(
@@ -394,21 +585,78 @@ pub mod exposed {
                 Namespace::ValueNS,
                 impl_,
             )
-            .expect("find <_ as pyly_lib::Expose<_>>::AS");
+            .ok_or_else(|| PyLyError::Eval {
+                span: tcx.def_span(impl_),
+                item: format!("{}::AS", tcx.def_path_str(impl_)),
+            })?;
 
         let ty_env = TypingEnv::post_analysis(tcx, impl_);
+        let span = tcx.def_span(as_const.def_id);
+        let item = || tcx.def_path_str(as_const.def_id);
+
+        let args = type_params(
+            tcx,
+            pyly,
+            GenericArgs::identity_for_item(tcx, as_const.def_id),
+        );
+        let instance = Instance::new(as_const.def_id, args);
 
         let as_ty = tcx
-            .normalize_erasing_regions(ty_env, tcx.type_of(as_const.def_id).instantiate_identity());
+            .normalize_erasing_regions(ty_env, tcx.type_of(as_const.def_id).instantiate(tcx, args));
 
         let as_val = tcx
-            .const_eval_poly(as_const.def_id)
-            .expect("Be able to evaluate AS constant!");
+            .const_eval_instance(ty_env, instance, span)
+            .map_err(|_| PyLyError::Eval { span, item: item() })?;
 
-        let (icx, op) =
-            mk_eval_cx_for_const_val(tcx.at(tcx.def_span(as_const.def_id)), ty_env, as_val, as_ty)
-                .unwrap();
+        ctx.read_as(tcx, ty_env, span, item, (as_val, as_ty))
+    }
+
+    /// Evaluate `<ty as pyly_lib::Exposed<L>>::AS`,
+    /// if `ty` is exposed to the language `L` (of `T`) at all.
+    ///
+    /// `ty` should already be normalized within `parent`
+    /// (see [crate::pyly::simplify_ty]).
+    pub fn of<'a, 'tcx, T: LangType>(
+        tcx: TyCtxt<'tcx>,
+        pyly: &PyLy,
+        ctx: &'a mut PyLyCtx,
+        parent: DefId,
+        ty: Ty<'tcx>,
+    ) -> Result<Option<&'a T>, PyLyError> {
+        let as_const = tcx
+            .associated_items(pyly.traits.exposed)
+            .find_by_name_and_namespace(
+                tcx,
+                Ident::from_str(crate::pyly::traits::Exposed::AS),
+                Namespace::ValueNS,
+                pyly.traits.exposed,
+            )
+            .ok_or_else(|| PyLyError::Eval {
+                span: tcx.def_span(parent),
+                item: format!("{}::AS", tcx.def_path_str(pyly.traits.exposed)),
+            })?;
+
+        let ty_env = TypingEnv::post_analysis(tcx, parent);
+
+        let language = tcx.type_of(T::language(pyly)).instantiate_identity();
+        let args = tcx.mk_args(&[type_params(tcx, pyly, ty).into(), language.into()]);
+
+        // No impl (or not specific enough) means it isn't exposed.
+        let Ok(Some(instance)) = Instance::try_resolve(tcx, ty_env, as_const.def_id, args) else {
+            return Ok(None);
+        };
+
+        let span = tcx.def_span(parent);
+        let item = || format!("<{ty} as pyly_lib::Exposed<{language}>>::AS");
+
+        let as_ty = tcx
+            .normalize_erasing_regions(ty_env, tcx.type_of(as_const.def_id).instantiate(tcx, args));
+
+        let as_val = tcx
+            .const_eval_instance(ty_env, instance, span)
+            .map_err(|_| PyLyError::Eval { span, item: item() })?;
 
-        ctx.read_py_type(tcx, &icx, pyly, op, ByRef)
+        ctx.read_as(tcx, ty_env, span, item, (as_val, as_ty))
+            .map(Some)
     }
 }
@@ -0,0 +1,290 @@
+//!
+//! Items' doc comments, with their intra-doc links rewritten for the stubs.
+//!
+
+use pulldown_cmark::{BrokenLink, CowStr, Event, LinkType, Options, Parser, Tag, TagEnd};
+use rustc_hir::{
+    def::{DefKind, Namespace},
+    def_id::DefId,
+};
+use rustc_middle::ty::{AssocKind, TyCtxt};
+use rustc_resolve::rustdoc;
+use rustc_span::Symbol;
+
+use crate::pyly::{
+    comptime::exposed::LangType,
+    items,
+    stubs::{Link, LinkKind, Stubs},
+    PyLy,
+};
+
+/// An item's doc comment (unindented), if it has one,
+/// with links to other exposed items rewritten for `stubs`.
+///
+/// Links to items which aren't exposed are left as just their text.
+pub fn docs<S: Stubs>(tcx: TyCtxt<'_>, pyly: &PyLy, stubs: &mut S, did: DefId) -> Option<String> {
+    let local = did.as_local()?;
+    let attrs = tcx.get_attrs_unchecked(did);
+    let (fragments, _) =
+        rustdoc::attrs_to_doc_fragments(attrs.iter().map(|attr| (attr, None)), true);
+    let doc = rustdoc::prepare_to_doc_link_resolution(&fragments)
+        .into_values()
+        .next()?;
+
+    let module = tcx.parent_module_from_def_id(local).to_def_id();
+    let doc = rewrite_links(&doc, |dest, text| {
+        let link = resolve::<S::Type>(tcx, pyly, module, dest)?;
+        Some(link.map(|mut link| {
+            link.text = text;
+            stubs.link(link)
+        }))
+    });
+
+    let doc = doc.trim();
+    (!doc.is_empty()).then(|| doc.to_string())
+}
+
+/// Rewrite each link in the Markdown `doc` with `rewrite(dest, text)`:
+/// * `None` leaves the link as it is,
+/// * `Some(None)` replaces it with its text,
+/// * and `Some(Some(link))` replaces it with `link`.
+///
+/// `text` is only given when it isn't just the link's destination
+/// (as it is for `` [`Page`] ``).
+fn rewrite_links(
+    doc: &str,
+    mut rewrite: impl FnMut(&str, Option<String>) -> Option<Option<String>>,
+) -> String {
+    // Intra-doc links don't need a definition (e.g. `[Page]: Page`).
+    let mut broken_link =
+        |link: BrokenLink<'_>| Some((CowStr::from(link.reference.to_string()), "".into()));
+    let mut events =
+        Parser::new_with_broken_link_callback(doc, Options::empty(), Some(&mut broken_link))
+            .into_offset_iter();
+
+    let mut out = String::new();
+    let mut written = 0;
+
+    while let Some((event, range)) = events.next() {
+        let Event::Start(Tag::Link {
+            link_type,
+            dest_url,
+            ..
+        }) = event
+        else {
+            continue;
+        };
+
+        let mut text = String::new();
+        for (event, _) in events.by_ref() {
+            match event {
+                Event::Text(t) => text.push_str(&t),
+                Event::Code(code) => text.push_str(&format!("`{code}`")),
+                Event::End(TagEnd::Link) => break,
+                _ => (),
+            }
+        }
+
+        if matches!(link_type, LinkType::Autolink | LinkType::Email) {
+            continue;
+        }
+
+        let shortcut = matches!(
+            link_type,
+            LinkType::Shortcut
+                | LinkType::ShortcutUnknown
+                | LinkType::Collapsed
+                | LinkType::CollapsedUnknown
+        );
+        let Some(link) = rewrite(&dest_url, (!shortcut).then(|| text.clone())) else {
+            continue;
+        };
+
+        out.push_str(&doc[written..range.start]);
+        out.push_str(&link.unwrap_or(text));
+        written = range.end;
+    }
+
+    out.push_str(&doc[written..]);
+    out
+}
+
+/// Resolve an intra-doc link's destination within `module`:
+/// `None` if it isn't to a Rust item at all,
+/// or `Some(None)` if it's to one that isn't exposed.
+fn resolve<L: LangType>(
+    tcx: TyCtxt<'_>,
+    pyly: &PyLy,
+    module: DefId,
+    dest: &str,
+) -> Option<Option<Link>> {
+    let path = preprocess(dest);
+    let resolutions = tcx.doc_link_resolutions(module);
+
+    let res = |path: &str| {
+        [Namespace::TypeNS, Namespace::ValueNS]
+            .into_iter()
+            .find_map(|ns| *resolutions.get(&(Symbol::intern(path), ns))?)
+            .and_then(|res| res.opt_def_id())
+    };
+
+    if let Some(did) = res(&path) {
+        return Some(link::<L>(tcx, pyly, did));
+    }
+
+    // Fields and methods (`Page::get`) are resolved relative to their type.
+    let (parent, name) = path.rsplit_once("::")?;
+    let parent = res(parent)?;
+    let name = Symbol::intern(name);
+
+    let did = match tcx.def_kind(parent) {
+        DefKind::Struct | DefKind::Enum => {
+            let adt = tcx.adt_def(parent);
+            let variant = adt.variants().iter().find(|var| var.name == name);
+            let field = adt
+                .is_struct()
+                .then(|| {
+                    adt.non_enum_variant()
+                        .fields
+                        .iter()
+                        .find(|field| field.name == name)
+                })
+                .flatten();
+            let method = tcx.inherent_impls(parent).iter().find_map(|&impl_| {
+                tcx.associated_items(impl_)
+                    .filter_by_name_unhygienic(name)
+                    .next()
+            });
+
+            variant
+                .map(|var| var.def_id)
+                .or(field.map(|field| field.did))
+                .or(method.map(|item| item.def_id))
+        }
+        DefKind::Variant => {
+            let adt = tcx.adt_def(tcx.parent(parent));
+            let variant = adt.variant_with_id(parent);
+            variant
+                .fields
+                .iter()
+                .find(|field| field.name == name)
+                .map(|field| field.did)
+        }
+        DefKind::Trait => tcx
+            .associated_items(parent)
+            .filter_by_name_unhygienic(name)
+            .next()
+            .map(|item| item.def_id),
+        _ => None,
+    };
+
+    Some(did.and_then(|did| link::<L>(tcx, pyly, did)))
+}
+
+/// A link to `did` in the stubs, if it's exposed (to `L`'s language).
+fn link<L: LangType>(tcx: TyCtxt<'_>, pyly: &PyLy, did: DefId) -> Option<Link> {
+    if !did.is_local() {
+        return None;
+    }
+
+    let link = |parent: DefId, path: String, kind: LinkKind| Link {
+        module: items::module(tcx, parent),
+        path,
+        kind,
+        text: None,
+    };
+
+    match tcx.def_kind(did) {
+        DefKind::Struct | DefKind::Enum | DefKind::Trait if items::is_exposed(tcx, did) => {
+            Some(link(did, items::name(tcx, did), LinkKind::Class))
+        }
+        // Declared as an alias, of what its own `impl Exposed<L>` says it is.
+        DefKind::Struct | DefKind::Enum
+            if items::aliases::<L>(tcx, pyly).any(|(_, adt)| adt == did) =>
+        {
+            let name = tcx.item_name(did).to_ident_string();
+            Some(link(did, name, LinkKind::Class))
+        }
+        DefKind::Fn if items::is_exposed(tcx, did) => {
+            Some(link(did, items::name(tcx, did), LinkKind::Function))
+        }
+        DefKind::AssocFn | DefKind::AssocConst => {
+            let item = tcx.associated_item(did);
+            let parent = match tcx.impl_of_method(did) {
+                Some(impl_) if items::is_exposed(tcx, impl_) => tcx
+                    .type_of(impl_)
+                    .instantiate_identity()
+                    .ty_adt_def()?
+                    .did(),
+                Some(_) => return None,
+                None => tcx.trait_of_item(did)?,
+            };
+
+            if !items::is_exposed(tcx, parent) {
+                return None;
+            }
+
+            let kind = match item.kind {
+                AssocKind::Fn => LinkKind::Method,
+                _ => LinkKind::Attribute,
+            };
+            let path = format!("{}.{}", items::name(tcx, parent), item.name);
+            Some(link(parent, path, kind))
+        }
+        DefKind::Field => {
+            let parent = tcx.parent(did);
+            let (adt, variant) = match tcx.def_kind(parent) {
+                DefKind::Variant => (tcx.parent(parent), Some(parent)),
+                _ => (parent, None),
+            };
+
+            let adt_def = tcx.adt_def(adt);
+            let variant_def = match variant {
+                Some(variant) => adt_def.variant_with_id(variant),
+                None => adt_def.non_enum_variant(),
+            };
+            let field = variant_def.fields.iter().find(|field| field.did == did)?;
+            if !items::is_exposed(tcx, adt) || items::is_skipped(tcx, did) {
+                return None;
+            }
+
+            let class = match variant {
+                Some(_) => format!("{}{}", items::name(tcx, adt), variant_def.name),
+                None => items::name(tcx, adt),
+            };
+            let path = format!("{class}.{}", items::field_name(tcx, variant_def, field));
+            Some(link(adt, path, LinkKind::Attribute))
+        }
+        DefKind::Variant => {
+            let adt = tcx.parent(did);
+            if !items::is_exposed(tcx, adt) {
+                return None;
+            }
+
+            let adt_def = tcx.adt_def(adt);
+            let name = items::name(tcx, adt);
+            let variant = adt_def.variant_with_id(did).name;
+
+            // Members of enumerations, or classes of their own.
+            Some(match items::is_enumeration(adt_def) {
+                true => link(adt, format!("{name}.{variant}"), LinkKind::Attribute),
+                false => link(adt, format!("{name}{variant}"), LinkKind::Class),
+            })
+        }
+        _ => None,
+    }
+}
+
+/// The path an intra-doc link refers to, without any decoration
+/// (as in `` [`struct@Page`] ``, or `[page()]`).
+fn preprocess(dest: &str) -> String {
+    let dest = dest.replace('`', "");
+    let dest = dest.split('#').next().unwrap_or_default().trim();
+    let dest = dest.rsplit('@').next().unwrap_or_default();
+    let dest = dest.strip_suffix("()").unwrap_or(dest);
+    let dest = dest.strip_suffix('!').unwrap_or(dest).trim();
+
+    rustdoc::strip_generics_from_path(dest)
+        .map(String::from)
+        .unwrap_or_else(|_| dest.to_string())
+}
@@ -0,0 +1,56 @@
+//!
+//! Errors in generating stubs, reported as compiler diagnostics.
+//!
+
+use std::{fmt, io, path::PathBuf};
+
+use rustc_errors::ErrorGuaranteed;
+use rustc_middle::ty::TyCtxt;
+use rustc_span::Span;
+
+#[derive(Debug)]
+pub enum PyLyError {
+    /// `pyly_lib` is used, but its traits and types can't be found.
+    MissingLibrary,
+    /// An `AS` constant couldn't be evaluated.
+    Eval { span: Span, item: String },
+    /// An `AS` constant was evaluated, but couldn't be read back.
+    Read {
+        span: Span,
+        item: String,
+        reason: String,
+    },
+    /// The stubs couldn't be written out.
+    Write { path: PathBuf, error: io::Error },
+}
+
+impl PyLyError {
+    /// Report this error against the crate being compiled.
+    pub fn emit(self, tcx: TyCtxt<'_>) -> ErrorGuaranteed {
+        let dcx = tcx.dcx();
+        let message = self.to_string();
+
+        match self {
+            PyLyError::MissingLibrary | PyLyError::Write { .. } => dcx.err(message),
+            PyLyError::Eval { span, .. } => dcx.span_err(span, message),
+            PyLyError::Read { span, reason, .. } => {
+                dcx.struct_span_err(span, message).with_note(reason).emit()
+            }
+        }
+    }
+}
+
+impl fmt::Display for PyLyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PyLyError::MissingLibrary => {
+                write!(f, "could not find the traits and types of `pyly_lib`")
+            }
+            PyLyError::Eval { item, .. } => write!(f, "could not evaluate `{item}`"),
+            PyLyError::Read { item, .. } => write!(f, "could not read the value of `{item}`"),
+            PyLyError::Write { path, error } => {
+                write!(f, "could not write stubs to `{}`: {error}", path.display())
+            }
+        }
+    }
+}
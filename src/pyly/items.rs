@@ -0,0 +1,738 @@
+//!
+//! Items marked with `#[pyly::expose]` (or exposed with PyO3's macros),
+//! and how they appear in the stubs.
+//!
+
+use rustc_hir::{
+    def::{CtorKind, DefKind},
+    def_id::{DefId, LocalDefId},
+};
+use rustc_middle::{
+    mir::interpret::GlobalId,
+    ty::{
+        self, AdtDef, AssocKind, FieldDef, GenericArgs, GenericArgsRef, GenericParamDefKind,
+        Instance, Ty, TyCtxt, TyKind, TypingEnv, VariantDef, VariantDiscr,
+    },
+};
+use rustc_span::{kw, Symbol};
+
+use crate::pyly::{
+    attrs,
+    comptime::{
+        self,
+        exposed::{LangType, PyLyCtx},
+    },
+    docs::docs,
+    pyo3, simplify_ty,
+    stubs::{Attr, Function, Literal, Member, Stubs},
+    PyLy,
+};
+
+/// Is this item marked with `#[pyly::expose]`,
+/// or is it a `#[pyclass]` or `#[pyfunction]`?
+pub fn is_exposed(tcx: TyCtxt<'_>, did: DefId) -> bool {
+    tcx.has_attrs_with_path(did, &attrs::expose.map(Symbol::intern))
+        || pyo3::is_class(tcx, did)
+        || pyo3::is_function(tcx, did)
+}
+
+/// Is this field marked with `#[pyly(skip)]`?
+pub fn is_skipped(tcx: TyCtxt<'_>, did: DefId) -> bool {
+    tcx.has_attrs_with_path(did, &attrs::skip.map(Symbol::intern))
+}
+
+/// Is this a fieldless enum, declared as an enumeration (e.g. an `enum.Enum`)?
+pub fn is_enumeration(adt: AdtDef<'_>) -> bool {
+    adt.is_enum() && adt.variants().iter().all(|var| var.fields.is_empty())
+}
+
+/// The value of this item's `#[{attr} = "..."]`, if it has one.
+fn attr_value(tcx: TyCtxt<'_>, did: DefId, attr: [&str; 2]) -> Option<Symbol> {
+    tcx.get_attrs_by_path(did, &attr.map(Symbol::intern))
+        .find_map(|attr| attr.value_str())
+}
+
+/// What an exposed item is called in the stubs:
+/// its `#[pyly::expose(name = "...")]`, what PyO3 calls it,
+/// or otherwise its own name.
+pub fn name(tcx: TyCtxt<'_>, did: DefId) -> String {
+    match attr_value(tcx, did, attrs::name) {
+        Some(name) => name.to_string(),
+        None => pyo3::name(tcx, did).unwrap_or_else(|| tcx.item_name(did).to_ident_string()),
+    }
+}
+
+/// The module an exposed item is declared in:
+/// its (dotted) `#[pyly::expose(module = "...")]`,
+/// the `#[pymodule]` PyO3 adds it to,
+/// or otherwise the path of its Rust module (e.g. `mycrate::geometry`).
+pub fn module(tcx: TyCtxt<'_>, did: DefId) -> String {
+    if let Some(module) = attr_value(tcx, did, attrs::module) {
+        return module.to_string();
+    }
+
+    if let Some(module) = pyo3::module(tcx, did) {
+        return module;
+    }
+
+    let mut module = tcx.parent(did);
+    while tcx.def_kind(module) != DefKind::Mod {
+        module = tcx.parent(module);
+    }
+
+    rust_module(tcx, module)
+}
+
+/// The path of a Rust module, from its crate (e.g. `mycrate::geometry`).
+fn rust_module(tcx: TyCtxt<'_>, module: DefId) -> String {
+    let path = tcx.def_path(module);
+    let path = path
+        .data
+        .iter()
+        .filter_map(|data| data.data.get_opt_name())
+        .map(|name| name.to_ident_string());
+
+    [tcx.crate_name(module.krate).to_ident_string()]
+        .into_iter()
+        .chain(path)
+        .collect::<Vec<_>>()
+        .join("::")
+}
+
+/// Every struct and enum in this crate marked with `#[pyly::expose]`.
+pub fn adts(tcx: TyCtxt<'_>) -> impl Iterator<Item = DefId> + '_ {
+    tcx.hir_crate_items(())
+        .definitions()
+        .map(LocalDefId::to_def_id)
+        .filter(move |&did| matches!(tcx.def_kind(did), DefKind::Struct | DefKind::Enum))
+        .filter(move |&did| is_exposed(tcx, did))
+}
+
+/// Every free function in this crate marked with `#[pyly::expose]`.
+pub fn fns(tcx: TyCtxt<'_>) -> impl Iterator<Item = DefId> + '_ {
+    tcx.hir_crate_items(())
+        .definitions()
+        .map(LocalDefId::to_def_id)
+        .filter(move |&did| tcx.def_kind(did) == DefKind::Fn)
+        .filter(move |&did| is_exposed(tcx, did))
+}
+
+/// Every `const` item in this crate marked with `#[pyly::expose]`.
+pub fn consts(tcx: TyCtxt<'_>) -> impl Iterator<Item = DefId> + '_ {
+    tcx.hir_crate_items(())
+        .definitions()
+        .map(LocalDefId::to_def_id)
+        .filter(move |&did| tcx.def_kind(did) == DefKind::Const)
+        .filter(move |&did| is_exposed(tcx, did))
+}
+
+/// Every trait in this crate marked with `#[pyly::expose]`.
+pub fn traits(tcx: TyCtxt<'_>) -> impl Iterator<Item = DefId> + '_ {
+    tcx.hir_crate_items(())
+        .definitions()
+        .map(LocalDefId::to_def_id)
+        .filter(move |&did| tcx.def_kind(did) == DefKind::Trait)
+        .filter(move |&did| is_exposed(tcx, did))
+}
+
+/// Every type in this crate exposed to `L`'s language by hand (with its own
+/// `impl Exposed<L>`, rather than `#[pyly::expose]`), which is declared
+/// as an alias of what it's exposed as: each impl, and its type.
+pub fn aliases<'tcx, L: LangType>(
+    tcx: TyCtxt<'tcx>,
+    pyly: &PyLy,
+) -> impl Iterator<Item = (DefId, DefId)> + 'tcx {
+    let language = L::language(pyly);
+    tcx.all_impls(pyly.traits.exposed)
+        .filter(|did| did.is_local())
+        .filter(move |&impl_| exposed_to(tcx, impl_) == Some(language))
+        .filter_map(move |impl_| Some((impl_, exposed_adt(tcx, impl_)?)))
+}
+
+/// The language `L` of an `impl Exposed<L> for _`.
+fn exposed_to(tcx: TyCtxt<'_>, impl_: DefId) -> Option<DefId> {
+    let trait_ref = tcx.impl_trait_ref(impl_)?.instantiate_identity();
+    let language = trait_ref.args.type_at(1).ty_adt_def()?;
+    Some(language.did())
+}
+
+/// The type `T` of an `impl Exposed<_> for T`, whose name it's known by in the stubs,
+/// if it has one (and isn't already a class).
+fn exposed_adt(tcx: TyCtxt<'_>, impl_: DefId) -> Option<DefId> {
+    let self_ty = tcx.type_of(impl_).instantiate_identity();
+    let adt = self_ty.ty_adt_def()?;
+    if is_exposed(tcx, adt.did()) {
+        return None;
+    }
+
+    Some(adt.did())
+}
+
+/// Every `pub use` of an exposed item in this crate:
+/// the (Rust) module it's re-exported from, the item,
+/// and the name it's re-exported as.
+pub fn reexports(tcx: TyCtxt<'_>) -> impl Iterator<Item = (String, DefId, String)> + '_ {
+    let mut modules = vec![];
+    tcx.hir_for_each_module(|module| modules.push(module.to_local_def_id()));
+
+    modules
+        .into_iter()
+        .flat_map(move |module| {
+            let children = tcx.module_children_local(module);
+            children.iter().map(move |child| (module, child))
+        })
+        .filter(|(_, child)| !child.reexport_chain.is_empty() && child.vis.is_public())
+        .filter_map(move |(module, child)| {
+            let did = child.res.opt_def_id()?;
+            let declared = matches!(
+                tcx.def_kind(did),
+                DefKind::Struct | DefKind::Enum | DefKind::Trait | DefKind::Fn | DefKind::Const
+            );
+            if !did.is_local() || !declared || !is_exposed(tcx, did) {
+                return None;
+            }
+
+            // Renamed in the stubs, unless it's renamed again by the `use`.
+            let alias = match child.ident.name == tcx.item_name(did) {
+                true => name(tcx, did),
+                false => child.ident.to_string(),
+            };
+
+            Some((rust_module(tcx, module.to_def_id()), did, alias))
+        })
+}
+
+/// Declare an exposed free function in the stubs.
+pub fn declare_fn<S: Stubs>(
+    tcx: TyCtxt<'_>,
+    pyly: &PyLy,
+    ctx: &mut PyLyCtx,
+    stubs: &mut S,
+    did: DefId,
+) {
+    let function = signature(tcx, pyly, ctx, stubs, did);
+    stubs.function(function);
+}
+
+/// Declare an exposed `const` item in the stubs, as exactly its value
+/// when that's an integer, string or `bool` (and as its type otherwise).
+pub fn declare_const<S: Stubs>(
+    tcx: TyCtxt<'_>,
+    pyly: &PyLy,
+    ctx: &mut PyLyCtx,
+    stubs: &mut S,
+    did: DefId,
+) {
+    let ty = simplify_ty(tcx, did, tcx.type_of(did));
+    let annotation = match literal(tcx, did, ty) {
+        Some(value) => stubs.literal(value),
+        None => annotation(tcx, pyly, ctx, stubs, did, ty),
+    };
+
+    let doc = docs(tcx, pyly, stubs, did);
+    stubs.constant(&name(tcx, did), doc, annotation);
+}
+
+/// The value of a `const` item of type `ty`, if it's a literal.
+fn literal<'tcx>(tcx: TyCtxt<'tcx>, did: DefId, ty: Ty<'tcx>) -> Option<Literal> {
+    let instance = Instance::new(did, GenericArgs::identity_for_item(tcx, did));
+    let id = GlobalId {
+        instance,
+        promoted: None,
+    };
+    let typing_env = TypingEnv::post_analysis(tcx, did);
+    let valtree = tcx
+        .const_eval_global_id_for_typeck(typing_env, id, tcx.def_span(did))
+        .ok()?
+        .ok()?;
+    let value = ty::Value { ty, valtree };
+
+    match ty.kind() {
+        TyKind::Bool => value.try_to_bool().map(Literal::Bool),
+        TyKind::Int(_) => {
+            let int = valtree.try_to_scalar_int()?;
+            Some(Literal::Int(int.to_int(int.size()).into()))
+        }
+        TyKind::Uint(_) => {
+            let int = valtree.try_to_scalar_int()?;
+            Some(Literal::Int(int.to_uint(int.size()).into()))
+        }
+        TyKind::Ref(_, inner, _) if inner.is_str() => {
+            let bytes = value.try_to_raw_bytes(tcx)?;
+            let str = std::str::from_utf8(bytes).ok()?;
+            Some(Literal::Str(str.to_string()))
+        }
+        _ => None,
+    }
+}
+
+/// Declare an exposed trait in the stubs, as a protocol
+/// with its associated consts and functions.
+///
+/// Associated types are left out.
+pub fn declare_trait<S: Stubs>(
+    tcx: TyCtxt<'_>,
+    pyly: &PyLy,
+    ctx: &mut PyLyCtx,
+    stubs: &mut S,
+    did: DefId,
+) {
+    let items = tcx
+        .associated_items(did)
+        .in_definition_order()
+        .collect::<Vec<_>>();
+
+    let attrs = items
+        .iter()
+        .filter(|item| item.kind == AssocKind::Const)
+        .map(|item| {
+            let ty = simplify_ty(tcx, did, tcx.type_of(item.def_id));
+            Attr {
+                name: item.name.to_ident_string(),
+                annotation: annotation(tcx, pyly, ctx, stubs, did, ty),
+                doc: docs(tcx, pyly, stubs, item.def_id),
+                readonly: false,
+            }
+        })
+        .collect();
+
+    let methods = items
+        .iter()
+        .filter(|item| item.kind == AssocKind::Fn)
+        .map(|item| signature(tcx, pyly, ctx, stubs, item.def_id))
+        .collect();
+
+    let doc = docs(tcx, pyly, stubs, did);
+    stubs.protocol(&name(tcx, did), doc, &generics(tcx, did), attrs, methods);
+}
+
+/// Declare an exposed struct or enum in the stubs.
+pub fn declare<S: Stubs>(
+    tcx: TyCtxt<'_>,
+    pyly: &PyLy,
+    ctx: &mut PyLyCtx,
+    stubs: &mut S,
+    did: DefId,
+) {
+    let adt = tcx.adt_def(did);
+    let name = name(tcx, did);
+    let doc = docs(tcx, pyly, stubs, did);
+    let generics = generics(tcx, did);
+    let methods = methods(tcx, pyly, ctx, stubs, did);
+
+    // A `#[pyclass]` has just what PyO3 gives it.
+    if adt.is_struct() && pyo3::is_class(tcx, did) {
+        let attrs = pyo3_attrs(tcx, pyly, ctx, stubs, did);
+        stubs.class(&name, doc, &generics, vec![], attrs, methods);
+        return;
+    }
+
+    if adt.is_struct() {
+        let attrs = attrs(tcx, pyly, ctx, stubs, did, adt.non_enum_variant());
+        stubs.class(&name, doc, &generics, vec![], attrs, methods);
+        return;
+    }
+
+    // Fieldless enums become enumerations (e.g. `enum.Enum`s),
+    // noting whether their discriminants are spelled out.
+    if is_enumeration(adt) {
+        let int = adt.repr().int.is_some()
+            || adt
+                .variants()
+                .iter()
+                .any(|var| matches!(var.discr, VariantDiscr::Explicit(_)));
+
+        let members = adt
+            .discriminants(tcx)
+            .map(|(idx, discr)| {
+                let value = discr
+                    .to_string()
+                    .parse()
+                    .expect("discriminant is an integer");
+
+                let variant = adt.variant(idx);
+                Member {
+                    name: variant.name.to_ident_string(),
+                    value,
+                    doc: docs(tcx, pyly, stubs, variant.def_id),
+                }
+            })
+            .collect();
+
+        stubs.enumeration(&name, doc, int, members, methods);
+        return;
+    }
+
+    // Otherwise, each variant gets its own class (`{Enum}{Variant}`),
+    // and the enum is their union. Every variant has the enum's generic parameters,
+    // and inherits its methods from a class of their own (`_{Enum}`).
+    let reference = |stubs: &mut S, ctx: &mut PyLyCtx, class: &str| {
+        let args = GenericArgs::identity_for_item(tcx, did);
+        let args = type_args(tcx, pyly, ctx, stubs, did, args);
+        stubs.reference(&module(tcx, did), class, args)
+    };
+
+    let bases = match methods.is_empty() {
+        true => vec![],
+        false => {
+            let base = format!("_{name}");
+            stubs.class(&base, None, &generics, vec![], vec![], methods);
+            vec![reference(stubs, ctx, &base)]
+        }
+    };
+
+    let variants = adt
+        .variants()
+        .iter()
+        .map(|var| {
+            let class = format!("{name}{}", var.name);
+            let attrs = attrs(tcx, pyly, ctx, stubs, did, var);
+            let doc = docs(tcx, pyly, stubs, var.def_id);
+            stubs.class(&class, doc, &generics, bases.clone(), attrs, vec![]);
+            reference(stubs, ctx, &class)
+        })
+        .collect();
+
+    let union = stubs.union(variants);
+    stubs.alias(&name, doc, &generics, union);
+}
+
+/// The names of an item's own generic type parameters
+/// (not including a trait's `Self`).
+pub fn generics(tcx: TyCtxt<'_>, did: DefId) -> Vec<String> {
+    tcx.generics_of(did)
+        .own_params
+        .iter()
+        .filter(|param| matches!(param.kind, GenericParamDefKind::Type { .. }))
+        .filter(|param| param.name != kw::SelfUpper)
+        .map(|param| param.name.to_ident_string())
+        .collect()
+}
+
+/// An annotation for each type argument in `args`.
+pub fn type_args<'tcx, S: Stubs>(
+    tcx: TyCtxt<'tcx>,
+    pyly: &PyLy,
+    ctx: &mut PyLyCtx,
+    stubs: &mut S,
+    parent: DefId,
+    args: GenericArgsRef<'tcx>,
+) -> Vec<S::Expr> {
+    args.types()
+        .map(|ty| annotation(tcx, pyly, ctx, stubs, parent, ty))
+        .collect()
+}
+
+/// What a field of `variant` is called in the stubs:
+/// its `#[pyly(rename = "...")]`, if it has one,
+/// and for tuple-like fields otherwise its position (`_0`, `_1`, ...).
+pub fn field_name(tcx: TyCtxt<'_>, variant: &VariantDef, field: &FieldDef) -> String {
+    let positional = variant.ctor_kind() == Some(CtorKind::Fn);
+
+    match (attr_value(tcx, field.did, attrs::rename), positional) {
+        (Some(rename), _) => rename.to_string(),
+        (None, true) => format!("_{}", field.name),
+        (None, false) => field.name.to_ident_string(),
+    }
+}
+
+/// An annotated attribute for each field of `variant`,
+/// except those with `#[pyly(skip)]`.
+fn attrs<S: Stubs>(
+    tcx: TyCtxt<'_>,
+    pyly: &PyLy,
+    ctx: &mut PyLyCtx,
+    stubs: &mut S,
+    parent: DefId,
+    variant: &VariantDef,
+) -> Vec<Attr<S::Expr>> {
+    variant
+        .fields
+        .iter()
+        .filter(|field| !is_skipped(tcx, field.did))
+        .map(|field| {
+            let ty = simplify_ty(tcx, parent, tcx.type_of(field.did));
+            Attr {
+                name: field_name(tcx, variant, field),
+                annotation: annotation(tcx, pyly, ctx, stubs, parent, ty),
+                doc: docs(tcx, pyly, stubs, field.did),
+                readonly: false,
+            }
+        })
+        .collect()
+}
+
+/// The methods in each of `adt`'s `#[pyly::expose]`d `impl` blocks
+/// (and its `#[pymethods]`, for a `#[pyclass]`).
+fn methods<S: Stubs>(
+    tcx: TyCtxt<'_>,
+    pyly: &PyLy,
+    ctx: &mut PyLyCtx,
+    stubs: &mut S,
+    adt: DefId,
+) -> Vec<Function<S::Expr>> {
+    let mut methods = tcx
+        .inherent_impls(adt)
+        .iter()
+        .filter(|&&impl_| is_exposed(tcx, impl_))
+        .flat_map(|&impl_| tcx.associated_items(impl_).in_definition_order())
+        .filter(|item| item.kind == AssocKind::Fn)
+        .map(|item| signature(tcx, pyly, ctx, stubs, item.def_id))
+        .collect::<Vec<_>>();
+
+    if pyo3::is_class(tcx, adt) {
+        methods.extend(pyo3_methods(tcx, pyly, ctx, stubs, adt));
+    }
+
+    methods
+}
+
+/// The attributes PyO3 gives a `#[pyclass]`:
+/// its fields with `#[pyo3(get)]` and its `#[getter]`s
+/// (read-only, without a setter of the same name),
+/// and its `#[classattr]`s.
+fn pyo3_attrs<S: Stubs>(
+    tcx: TyCtxt<'_>,
+    pyly: &PyLy,
+    ctx: &mut PyLyCtx,
+    stubs: &mut S,
+    adt: DefId,
+) -> Vec<Attr<S::Expr>> {
+    let members = pyo3::members(tcx, adt);
+    let settable = |name: &str| {
+        members
+            .iter()
+            .any(|member| member.kind == pyo3::Kind::Setter && member.name == name)
+    };
+
+    members
+        .iter()
+        .filter(|member| matches!(member.kind, pyo3::Kind::Getter | pyo3::Kind::ClassAttribute))
+        .filter_map(|member| {
+            let (annotation, doc) = match member.source {
+                pyo3::Source::Field(field) => {
+                    let ty = simplify_ty(tcx, adt, tcx.type_of(field));
+                    let annotation = annotation(tcx, pyly, ctx, stubs, adt, ty);
+                    (annotation, docs(tcx, pyly, stubs, field))
+                }
+                pyo3::Source::Fn(did) => {
+                    let function = signature(tcx, pyly, ctx, stubs, did);
+                    (function.returns?, function.doc)
+                }
+                pyo3::Source::Generated => return None,
+            };
+
+            Some(Attr {
+                name: member.name.clone(),
+                annotation,
+                doc,
+                readonly: member.kind == pyo3::Kind::Getter && !settable(&member.name),
+            })
+        })
+        .collect()
+}
+
+/// The methods in a `#[pyclass]`'s `#[pymethods]`, as PyO3 exposes them:
+/// * the receiver of a method (e.g. `slf: PyRef<'_, Self>`) is its `self`,
+/// * a `#[classmethod]` takes the class without being given it,
+/// * and its `#[new]` constructor is its `__init__` (which goes first).
+fn pyo3_methods<S: Stubs>(
+    tcx: TyCtxt<'_>,
+    pyly: &PyLy,
+    ctx: &mut PyLyCtx,
+    stubs: &mut S,
+    adt: DefId,
+) -> Vec<Function<S::Expr>> {
+    let mut members = pyo3::members(tcx, adt);
+    members.sort_by_key(|member| member.kind != pyo3::Kind::Slot || member.name != "__new__");
+
+    members
+        .into_iter()
+        .filter_map(|member| {
+            let pyo3::Source::Fn(did) = member.source else {
+                return None;
+            };
+
+            let new = member.kind == pyo3::Kind::Slot && member.name == "__new__";
+            let (receiver, skip) = match member.kind {
+                _ if new => (true, 0),
+                pyo3::Kind::Method | pyo3::Kind::Slot => (true, 1),
+                pyo3::Kind::Static => (false, 0),
+                pyo3::Kind::Class => (false, 1),
+                pyo3::Kind::Getter | pyo3::Kind::Setter | pyo3::Kind::ClassAttribute => {
+                    return None;
+                }
+            };
+
+            let mut function = signature_with(tcx, pyly, ctx, stubs, did, receiver, skip);
+            function.name = match new {
+                true => "__init__".to_string(),
+                false => member.name,
+            };
+            if new {
+                function.returns = None;
+            }
+
+            Some(function)
+        })
+        .collect()
+}
+
+/// The signature of a function (or method), with each of its types annotated.
+///
+/// Unnamed parameters (e.g. `_`, or patterns) are named by position (`_0`, `_1`, ...),
+/// and PyO3's `Python<'py>` tokens are left out.
+fn signature<S: Stubs>(
+    tcx: TyCtxt<'_>,
+    pyly: &PyLy,
+    ctx: &mut PyLyCtx,
+    stubs: &mut S,
+    did: DefId,
+) -> Function<S::Expr> {
+    let receiver = tcx
+        .opt_associated_item(did)
+        .is_some_and(|item| item.fn_has_self_parameter);
+
+    signature_with(tcx, pyly, ctx, stubs, did, receiver, receiver as usize)
+}
+
+/// The signature of a function, taking `self` if it's a `receiver`,
+/// but leaving out its first `skip` parameters (e.g. its `self`).
+fn signature_with<S: Stubs>(
+    tcx: TyCtxt<'_>,
+    pyly: &PyLy,
+    ctx: &mut PyLyCtx,
+    stubs: &mut S,
+    did: DefId,
+    receiver: bool,
+    skip: usize,
+) -> Function<S::Expr> {
+    let ty_env = TypingEnv::post_analysis(tcx, did);
+    let sig = tcx.fn_sig(did).instantiate(tcx, impl_args(tcx, did));
+    let sig = tcx.normalize_erasing_late_bound_regions(ty_env, sig);
+
+    let params = sig
+        .inputs()
+        .iter()
+        .zip(tcx.fn_arg_names(did))
+        .enumerate()
+        .skip(skip)
+        .filter(|(_, (&ty, _))| !pyo3::is_token(tcx, ty))
+        .map(|(i, (&ty, ident))| {
+            let name = match ident.name {
+                kw::Underscore | kw::Empty => format!("_{i}"),
+                name => name.to_ident_string(),
+            };
+
+            (name, annotation(tcx, pyly, ctx, stubs, did, ty))
+        })
+        .collect();
+
+    // Errors are reported when annotating what it returns.
+    let raises = match comptime::exposed::of::<S::Type>(tcx, pyly, ctx, did, sig.output()) {
+        Ok(Some(ty)) => ty.raises().map(|raises| stubs.annotation(raises)),
+        _ => None,
+    };
+
+    Function {
+        name: name(tcx, did),
+        doc: docs(tcx, pyly, stubs, did),
+        generics: generics(tcx, did),
+        receiver,
+        params,
+        returns: (!sig.output().is_unit())
+            .then(|| annotation(tcx, pyly, ctx, stubs, did, sig.output())),
+        raises,
+    }
+}
+
+/// The generic arguments of a method, but with its `impl`'s type parameters
+/// renamed to match those of the type it's implemented on
+/// (e.g. `T` for `impl<U> Page<U>`, given `struct Page<T>`).
+fn impl_args(tcx: TyCtxt<'_>, did: DefId) -> GenericArgsRef<'_> {
+    let identity = GenericArgs::identity_for_item(tcx, did);
+
+    let Some(impl_) = tcx.impl_of_method(did) else {
+        return identity;
+    };
+    let TyKind::Adt(adt, args) = tcx.type_of(impl_).instantiate_identity().kind() else {
+        return identity;
+    };
+
+    let renamed = args
+        .types()
+        .zip(GenericArgs::identity_for_item(tcx, adt.did()).types())
+        .filter_map(|(ty, adt_ty)| match (ty.kind(), adt_ty.kind()) {
+            (TyKind::Param(param), TyKind::Param(adt_param)) => Some((param.index, adt_param.name)),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+
+    let args = identity
+        .iter()
+        .map(|arg| match arg.as_type().map(Ty::kind) {
+            Some(&TyKind::Param(param)) => renamed
+                .iter()
+                .find(|&&(index, _)| index == param.index)
+                .map_or(arg, |&(index, name)| Ty::new_param(tcx, index, name).into()),
+            _ => arg,
+        });
+
+    tcx.mk_args_from_iter(args)
+}
+
+/// The annotation for a Rust type used within `parent`:
+/// * exposed structs, enums and trait objects by name (with any type arguments),
+/// * otherwise its [pyly_lib::Exposed] type,
+/// * references and boxes by what they refer to
+///   (and PyO3's smart pointers, and `PyResult`s, by what they hold),
+/// * or the language's default (e.g. `typing.Any`) if it isn't exposed at all.
+pub fn annotation<'tcx, S: Stubs>(
+    tcx: TyCtxt<'tcx>,
+    pyly: &PyLy,
+    ctx: &mut PyLyCtx,
+    stubs: &mut S,
+    parent: DefId,
+    ty: Ty<'tcx>,
+) -> S::Expr {
+    if let Some(inner) = pyo3::unwrap(tcx, ty) {
+        return annotation(tcx, pyly, ctx, stubs, parent, inner);
+    }
+
+    if let TyKind::Adt(adt, args) = ty.peel_refs().kind() {
+        if adt.did().is_local() && is_exposed(tcx, adt.did()) {
+            let args = type_args(tcx, pyly, ctx, stubs, parent, args);
+            return stubs.reference(&module(tcx, adt.did()), &name(tcx, adt.did()), args);
+        }
+    }
+
+    if let TyKind::Dynamic(predicates, ..) = ty.peel_refs().kind() {
+        if let Some(principal) = predicates.principal() {
+            let principal = principal.skip_binder();
+            if principal.def_id.is_local() && is_exposed(tcx, principal.def_id) {
+                let args = type_args(tcx, pyly, ctx, stubs, parent, principal.args);
+                let (module, name) = (module(tcx, principal.def_id), name(tcx, principal.def_id));
+                return stubs.reference(&module, &name, args);
+            }
+        }
+    }
+
+    match comptime::exposed::of::<S::Type>(tcx, pyly, ctx, parent, ty) {
+        Ok(Some(ty)) => return stubs.annotation(ty),
+        Ok(None) => (),
+        // Reported, so annotate it as anything else that isn't exposed.
+        Err(err) => {
+            err.emit(tcx);
+            return stubs.annotation(&Default::default());
+        }
+    }
+
+    if let Some(inner) = ty.boxed_ty() {
+        return annotation(tcx, pyly, ctx, stubs, parent, inner);
+    }
+
+    match ty.kind() {
+        TyKind::Ref(_, inner, _) => annotation(tcx, pyly, ctx, stubs, parent, *inner),
+        _ => stubs.annotation(&Default::default()),
+    }
+}
@@ -1,8 +1,14 @@
 //!
 //! Utilities for getting the type information for [::pyly_lib].
 //!
-//! 
+//!
 pub mod comptime;
+pub mod docs;
+pub mod error;
+pub mod items;
+pub mod pyo3;
+pub mod serde;
+pub mod stubs;
 
 use std::collections::{HashMap, HashSet};
 
@@ -11,6 +17,8 @@ use rustc_hir::def_id::{CrateNum, DefId};
 use rustc_middle::ty::{self, AdtDef, AdtDefData, Ty, TyCtxt, TyKind, TypingEnv};
 use rustc_type_ir::fast_reject::SimplifiedType;
 
+use crate::pyly::error::PyLyError;
+
 #[allow(non_camel_case_types, non_snake_case, non_upper_case_globals)]
 mod traits {
     pub const Language: &str = "pyly_lib::Language";
@@ -22,8 +30,24 @@ mod traits {
     }
 }
 
+#[allow(non_camel_case_types, non_snake_case, non_upper_case_globals)]
+mod attrs {
+    /// `#[pyly::expose]`
+    pub const expose: [&str; 2] = ["__pyly", "__expose"];
+    /// `#[pyly::expose(name = "...")]`
+    pub const name: [&str; 2] = ["__pyly", "__name"];
+    /// `#[pyly::expose(module = "...")]`
+    pub const module: [&str; 2] = ["__pyly", "__module"];
+    /// `#[pyly(skip)]`, on a field
+    pub const skip: [&str; 2] = ["__pyly", "__skip"];
+    /// `#[pyly(rename = "...")]`, on a field
+    pub const rename: [&str; 2] = ["__pyly", "__rename"];
+}
+
 #[allow(non_camel_case_types, non_snake_case, non_upper_case_globals)]
 mod types {
+    pub const TypeParam: &str = "pyly_lib::TypeParam";
+
     pub const Python: &str = "pyly_lib::Python";
 
     pub mod python {
@@ -31,6 +55,12 @@ mod types {
         pub const Type: &str = "pyly_lib::python::Type";
         pub const Typing: &str = "pyly_lib::python::Typing";
     }
+
+    pub const TypeScript: &str = "pyly_lib::TypeScript";
+
+    pub mod typescript {
+        pub const Type: &str = "pyly_lib::typescript::Type";
+    }
 }
 
 type PathDefMap = HashMap<String, DefId>;
@@ -40,20 +70,32 @@ type PathDefMap = HashMap<String, DefId>;
 pub struct PyLy {
     pub krate: CrateNum,
     pub traits: PyLyTraits,
+    /// [pyly_lib::TypeParam]
+    pub type_param: DefId,
 
     pub py: PyLyPy,
+    pub ts: PyLyTs,
 }
 
 impl PyLy {
-    /// Try to load the library's traits and types.
-    pub fn new(tcx: TyCtxt<'_>) -> Option<Self> {
+    /// Try to load the library's traits and types,
+    /// if this crate uses it at all.
+    pub fn new(tcx: TyCtxt<'_>) -> Result<Option<Self>, PyLyError> {
         // Fetch pyly_lib's crate number, if it is used used at all.
-        let krate = tcx
+        let Some(&krate) = tcx
             .used_crates(())
             .iter()
             .find(|&&num| tcx.crate_name(num).to_ident_string() == "pyly_lib")
-            .copied()?;
+        else {
+            return Ok(None);
+        };
 
+        Self::load(tcx, krate)
+            .map(Some)
+            .ok_or(PyLyError::MissingLibrary)
+    }
+
+    fn load(tcx: TyCtxt<'_>, krate: CrateNum) -> Option<Self> {
         // Collect all relevant traits defined in the library.
         let traits = tcx
             .traits(krate)
@@ -67,10 +109,18 @@ impl PyLy {
         let types = &mut visitor.finish(tcx);
 
         let traits = PyLyTraits::from_map(traits)?;
+        let type_param = types.remove(types::TypeParam)?;
 
         let py = PyLyPy::new(types)?;
+        let ts = PyLyTs::new(types)?;
 
-        Some(Self { krate, traits, py })
+        Some(Self {
+            krate,
+            traits,
+            type_param,
+            py,
+            ts,
+        })
     }
 }
 
@@ -115,13 +165,31 @@ impl PyLyPy {
     }
 }
 
+/// Contents of [::pyly_lib::typescript].
+#[derive(Debug)]
+pub struct PyLyTs {
+    /// [pyly_lib::TypeScript]
+    pub typescript: DefId,
+    /// [pyly_lib::typescript::Type]
+    pub type_: DefId,
+}
+
+impl PyLyTs {
+    pub fn new(types: &mut PathDefMap) -> Option<Self> {
+        Some(Self {
+            typescript: types.remove(types::TypeScript)?,
+            type_: types.remove(types::typescript::Type)?,
+        })
+    }
+}
+
 #[derive(Debug)]
 struct Visitor {
     krate: CrateNum,
     adts: HashSet<DefId>,
 }
 
-fn simplify_ty<'tcx>(
+pub(crate) fn simplify_ty<'tcx>(
     tcx: TyCtxt<'tcx>,
     parent: impl Into<Option<DefId>>,
     ty: ty::EarlyBinder<'tcx, Ty<'tcx>>,
@@ -0,0 +1,536 @@
+//!
+//! Items exposed with PyO3's own macros (`#[pyclass]`, `#[pymethods]`,
+//! `#[pyfunction]` and `#[pymodule]`), found in the code they expand to.
+//!
+//! That code is PyO3's own business, so this only reads what [VERSION] generates:
+//! the `__pymethod_*` wrappers, the `_PYO3_DEF` of each function,
+//! the `__PYO3_NAME` of each module, and the fields of each `PyClassItems` table.
+//! Anything PyO3's macros marked, but which doesn't have these, gets a warning.
+//!
+
+use std::{cell::OnceCell, collections::HashMap};
+
+use rustc_hir::{
+    self as hir,
+    def::{DefKind, Res},
+    def_id::{DefId, LocalDefId},
+    intravisit::{self, Visitor},
+};
+use rustc_middle::{
+    hir::nested_filter,
+    ty::{Ty, TyCtxt, TyKind, TypeckResults},
+};
+use rustc_span::{hygiene::ExpnKind, sym, Symbol};
+
+/// The PyO3 release whose generated code this reads
+/// (as `tests/pyo3` depends on, which the `pyo3_version` test checks).
+pub const VERSION: &str = "0.23";
+
+/// Is this item part of PyO3 itself?
+fn is_pyo3(tcx: TyCtxt<'_>, did: DefId) -> bool {
+    tcx.crate_name(did.krate).as_str() == "pyo3"
+}
+
+/// Is this item PyO3's `name`?
+fn is_named(tcx: TyCtxt<'_>, did: DefId, name: &str) -> bool {
+    is_pyo3(tcx, did) && tcx.item_name(did).as_str() == name
+}
+
+/// Is this a struct or enum with `#[pyclass]`?
+pub fn is_class(tcx: TyCtxt<'_>, did: DefId) -> bool {
+    matches!(tcx.def_kind(did), DefKind::Struct | DefKind::Enum)
+        && trait_impl(tcx, did, "PyClass").is_some()
+}
+
+/// Is this a free function with `#[pyfunction]`?
+pub fn is_function(tcx: TyCtxt<'_>, did: DefId) -> bool {
+    tcx.def_kind(did) == DefKind::Fn && function_def(tcx, did).is_some()
+}
+
+/// Is this `Python<'py>`, the token PyO3 passes functions that ask for it
+/// (so not one of their parameters in Python)?
+pub fn is_token(tcx: TyCtxt<'_>, ty: Ty<'_>) -> bool {
+    ty.ty_adt_def()
+        .is_some_and(|adt| is_named(tcx, adt.did(), "Python"))
+}
+
+/// What a PyO3 type stands for in Python:
+/// * `Bound<'py, T>`, `Borrowed<'a, 'py, T>`, `Py<T>`, `PyRef<'py, T>` and `PyRefMut<'py, T>`
+///   for the `T` they hold,
+/// * and a `PyResult<T>` for the `T` it returns (it may raise any exception).
+pub fn unwrap<'tcx>(tcx: TyCtxt<'tcx>, ty: Ty<'tcx>) -> Option<Ty<'tcx>> {
+    const SMART_POINTERS: [&str; 5] = ["Bound", "Borrowed", "Py", "PyRef", "PyRefMut"];
+
+    let TyKind::Adt(adt, args) = ty.peel_refs().kind() else {
+        return None;
+    };
+
+    if SMART_POINTERS
+        .iter()
+        .any(|name| is_named(tcx, adt.did(), name))
+    {
+        return args.types().next();
+    }
+
+    let mut types = args.types();
+    let (ok, err) = (types.next()?, types.next()?);
+    let py_err = err
+        .ty_adt_def()
+        .is_some_and(|err| is_named(tcx, err.did(), "PyErr"));
+
+    (tcx.is_diagnostic_item(sym::Result, adt.did()) && py_err).then_some(ok)
+}
+
+/// What PyO3 calls a `#[pyclass]` or `#[pyfunction]` in Python,
+/// (its `name = "..."`, or otherwise its own name).
+pub fn name(tcx: TyCtxt<'_>, did: DefId) -> Option<String> {
+    match tcx.def_kind(did) {
+        DefKind::Struct | DefKind::Enum => {
+            let impl_ = trait_impl(tcx, did, "PyTypeInfo")?;
+            let name = tcx
+                .associated_items(impl_)
+                .filter_by_name_unhygienic(Symbol::intern("NAME"))
+                .next()?;
+            literal(tcx, name.def_id)
+        }
+        DefKind::Fn => literal(tcx, function_def(tcx, did)?),
+        _ => None,
+    }
+}
+
+/// The name of the `#[pymodule]` a `#[pyclass]` or `#[pyfunction]` is in:
+/// * the (declarative) module it's declared in,
+/// * the one whose function adds it (e.g. with `add_class::<T>()`),
+/// * or else, if there's just the one, the crate's only module.
+pub fn module(tcx: TyCtxt<'_>, did: DefId) -> Option<String> {
+    let modules = modules(tcx);
+    let added = |init: Option<DefId>| {
+        let Some(init) = init.and_then(DefId::as_local) else {
+            return false;
+        };
+
+        let mentions = Mentions::of(tcx, init);
+        let def = function_def(tcx, did);
+        mentions
+            .defs
+            .iter()
+            .any(|&mentioned| mentioned == did || Some(mentioned) == def)
+    };
+
+    let module = modules
+        .iter()
+        .find(|(module, _)| tcx.is_descendant_of(did, *module))
+        .or_else(|| modules.iter().find(|&&(_, init)| added(init)))
+        .or(match modules.as_slice() {
+            [only] => Some(only),
+            _ => None,
+        })?;
+
+    literal(tcx, child(tcx, module.0, "__PYO3_NAME")?)
+}
+
+/// Every `#[pymodule]` in this crate,
+/// with its function (unless it's declared as a module itself).
+fn modules(tcx: TyCtxt<'_>) -> Vec<(DefId, Option<DefId>)> {
+    let mut modules = vec![];
+    tcx.hir_for_each_module(|module| modules.push(module.to_def_id()));
+
+    modules
+        .into_iter()
+        .filter(|&module| child(tcx, module, "__PYO3_NAME").is_some())
+        .map(|module| {
+            let parent = tcx.parent(module);
+            let init = tcx
+                .module_children_local(parent.expect_local())
+                .iter()
+                .find(|child| {
+                    child.ident.name == tcx.item_name(module)
+                        && matches!(child.res, Res::Def(DefKind::Fn, _))
+                })
+                .and_then(|child| child.res.opt_def_id());
+
+            (module, init)
+        })
+        .collect()
+}
+
+/// The item called `name` in a module of this crate.
+fn child(tcx: TyCtxt<'_>, module: DefId, name: &str) -> Option<DefId> {
+    tcx.module_children_local(module.as_local()?)
+        .iter()
+        .find(|child| child.ident.name.as_str() == name)
+        .and_then(|child| child.res.opt_def_id())
+}
+
+/// The `_PYO3_DEF` of a `#[pyfunction]`,
+/// in the module PyO3 declares (with the same name) alongside it.
+///
+/// A `#[pymodule]`'s is a `static`, not a `const`.
+fn function_def(tcx: TyCtxt<'_>, did: DefId) -> Option<DefId> {
+    let parent = tcx.parent_module_from_def_id(did.as_local()?);
+    let module = tcx
+        .module_children_local(parent.to_local_def_id())
+        .iter()
+        .find(|child| {
+            child.ident.name == tcx.item_name(did) && matches!(child.res, Res::Def(DefKind::Mod, _))
+        })?
+        .res
+        .opt_def_id()?;
+
+    child(tcx, module, "_PYO3_DEF").filter(|&def| tcx.def_kind(def) == DefKind::Const)
+}
+
+/// The local `impl` of PyO3's trait `name` for `adt`
+/// (or for something of it, as in `impl PyMethods<T> for PyClassImplCollector<T>`).
+fn trait_impl(tcx: TyCtxt<'_>, adt: DefId, name: &str) -> Option<DefId> {
+    thread_local! {
+        /// Every local `impl` of one of PyO3's traits, by the trait's name and each ADT
+        /// it's for: collected the first time it's needed, since every item is checked.
+        static TRAIT_IMPLS: OnceCell<HashMap<(Symbol, DefId), DefId>> = const { OnceCell::new() };
+    }
+
+    TRAIT_IMPLS.with(|impls| {
+        let impls = impls.get_or_init(|| trait_impls(tcx));
+        impls.get(&(Symbol::intern(name), adt)).copied()
+    })
+}
+
+fn trait_impls(tcx: TyCtxt<'_>) -> HashMap<(Symbol, DefId), DefId> {
+    let mut by_adt = HashMap::new();
+
+    let traits = tcx.all_local_trait_impls(()).iter();
+    for (&trait_, impls) in traits.filter(|(&trait_, _)| is_pyo3(tcx, trait_)) {
+        let name = tcx.item_name(trait_);
+
+        for impl_ in impls.iter().map(|impl_| impl_.to_def_id()) {
+            let Some(trait_ref) = tcx.impl_trait_ref(impl_) else {
+                continue;
+            };
+
+            let types = trait_ref.instantiate_identity().args.types();
+            for adt in types.filter_map(|ty| ty.ty_adt_def()) {
+                by_adt.entry((name, adt.did())).or_insert(impl_);
+            }
+        }
+    }
+
+    by_adt
+}
+
+/// Warn about each item PyO3's `#[pyclass]` or `#[pyfunction]` generated code for,
+/// which isn't the code this reads (say, from a PyO3 other than [VERSION]),
+/// rather than silently leaving it out of the stubs.
+pub fn check(tcx: TyCtxt<'_>) {
+    let mut unread = vec![];
+
+    for did in tcx.hir_crate_items(()).definitions() {
+        let did = did.to_def_id();
+        let expn = tcx.def_span(did).ctxt().outer_expn_data();
+        let ExpnKind::Macro(_, macro_) = expn.kind else {
+            continue;
+        };
+
+        match (macro_.as_str(), tcx.def_kind(did)) {
+            // `impl PyClassImpl for T`, and the like
+            // (but not for the helper types it declares itself).
+            ("pyclass", DefKind::Impl { of_trait: true }) => {
+                let self_ty = tcx.type_of(did).instantiate_identity();
+                let Some(adt) = self_ty.ty_adt_def().map(|adt| adt.did()) else {
+                    continue;
+                };
+                let declared = adt.is_local() && !tcx.def_span(adt).from_expansion();
+                if declared && !is_class(tcx, adt) && !unread.contains(&adt) {
+                    unread.push(adt);
+                }
+            }
+            // `mod f { ... }`, alongside the function `f`.
+            ("pyfunction", DefKind::Mod) => {
+                let parent = tcx.parent(did);
+                let function = tcx
+                    .module_children_local(parent.expect_local())
+                    .iter()
+                    .find(|child| {
+                        child.ident.name == tcx.item_name(did)
+                            && matches!(child.res, Res::Def(DefKind::Fn, _))
+                    })
+                    .and_then(|child| child.res.opt_def_id());
+
+                if let Some(function) = function.filter(|&function| !is_function(tcx, function)) {
+                    unread.push(function);
+                }
+            }
+            _ => (),
+        }
+    }
+
+    for did in unread {
+        tcx.dcx().span_warn(
+            tcx.def_span(did),
+            format!(
+                "`{}` is exposed with PyO3, but not as PyO3 {VERSION} does: \
+                 it's left out of the stubs",
+                tcx.def_path_str(did)
+            ),
+        );
+    }
+}
+
+/// The first string in a `const` (or `static`), looking through to another,
+/// as in `const _PYO3_DEF: PyMethodDef = MakeDef::_PYO3_DEF;`.
+fn literal(tcx: TyCtxt<'_>, did: DefId) -> Option<String> {
+    let mentions = Mentions::of(tcx, did.as_local()?);
+    if let Some(string) = mentions.strings.into_iter().next() {
+        return Some(string);
+    }
+
+    mentions
+        .defs
+        .into_iter()
+        .filter(|&def| matches!(tcx.def_kind(def), DefKind::Const | DefKind::AssocConst))
+        .find_map(|def| literal(tcx, def))
+}
+
+/// How something of a `#[pyclass]` is exposed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    /// A method taking `self`.
+    Method,
+    /// A `#[staticmethod]`.
+    Static,
+    /// A `#[classmethod]`, taking the class first.
+    Class,
+    /// A `#[getter]`, or a field's `#[pyo3(get)]`.
+    Getter,
+    /// A `#[setter]`, or a field's `#[pyo3(set)]`.
+    Setter,
+    /// A `#[classattr]`.
+    ClassAttribute,
+    /// A "magic" method (e.g. `__repr__`), or its constructor (`__new__`).
+    Slot,
+}
+
+/// Where a member of a `#[pyclass]` comes from in Rust.
+#[derive(Debug, Clone, Copy)]
+pub enum Source {
+    /// One of its fields.
+    Field(DefId),
+    /// A function in one of its `#[pymethods]`.
+    Fn(DefId),
+    /// Only PyO3's own code (e.g. the setter of a field).
+    Generated,
+}
+
+/// Something PyO3 adds to a `#[pyclass]` in Python.
+#[derive(Debug, Clone)]
+pub struct Member {
+    pub kind: Kind,
+    pub name: String,
+    pub source: Source,
+}
+
+/// Everything PyO3 adds to a `#[pyclass]`, in the order it's declared:
+/// its fields with `#[pyo3(get, set)]` first, then what its `#[pymethods]` have.
+///
+/// These are read from the tables PyO3 makes of them
+/// (each a `PyClassItems`, in its `PyClassImpl` and `PyMethods` impls).
+pub fn members(tcx: TyCtxt<'_>, adt: DefId) -> Vec<Member> {
+    let impls = ["PyClassImpl", "PyMethods"]
+        .into_iter()
+        .filter_map(|name| trait_impl(tcx, adt, name));
+
+    impls
+        .flat_map(|impl_| tcx.associated_items(impl_).in_definition_order())
+        .filter_map(|item| item.def_id.as_local())
+        .filter(|&did| tcx.hir_maybe_body_owned_by(did).is_some())
+        .flat_map(|did| Mentions::of(tcx, did).tables)
+        .filter_map(|(slot, entry)| member(tcx, adt, slot, entry))
+        .collect()
+}
+
+/// Read a member from an entry in one of its class's tables:
+/// its methods (`PyMethodDefType::Getter(...)`, ...) or its `slots`.
+fn member(tcx: TyCtxt<'_>, adt: DefId, slot: bool, entry: Mentions<'_>) -> Option<Member> {
+    const WRAPPER: &str = "__pymethod_";
+
+    let wrapper = entry.defs.iter().copied().find(|&def| {
+        def.is_local()
+            && tcx.def_kind(def) == DefKind::AssocFn
+            && tcx.item_name(def).as_str().starts_with(WRAPPER)
+    });
+
+    // The Rust function a wrapper calls (`let function = T::method;`).
+    let source = wrapper
+        .and_then(|wrapper| {
+            let mentions = Mentions::of(tcx, wrapper.expect_local());
+            mentions.defs.into_iter().find(|&def| {
+                def.is_local()
+                    && tcx.def_kind(def) == DefKind::AssocFn
+                    && !tcx.item_name(def).as_str().starts_with(WRAPPER)
+                    && tcx.impl_of_method(def).is_some_and(|impl_| {
+                        let self_ty = tcx.type_of(impl_).instantiate_identity();
+                        self_ty.ty_adt_def().is_some_and(|ty| ty.did() == adt)
+                    })
+            })
+        })
+        .map(Source::Fn);
+
+    // Field getters are made at runtime, from the field's offset.
+    let field = entry.fields.first().and_then(|&name| {
+        let mut fields = tcx.adt_def(adt).all_fields();
+        fields
+            .find(|field| field.name == name)
+            .map(|field| field.did)
+    });
+
+    if slot {
+        // `__pymethod___repr____` is `__repr__`.
+        let name = tcx.item_name(wrapper?);
+        let name = name.as_str().strip_prefix(WRAPPER)?.strip_suffix("__")?;
+        return Some(Member {
+            kind: Kind::Slot,
+            name: name.to_string(),
+            source: source?,
+        });
+    }
+
+    let variant = entry.defs.iter().find_map(|&def| {
+        let DefKind::Ctor(..) = tcx.def_kind(def) else {
+            return None;
+        };
+        let variant = tcx.parent(def);
+        is_named(tcx, tcx.parent(variant), "PyMethodDefType").then(|| tcx.item_name(variant))
+    });
+
+    let kind = match variant.as_ref().map(Symbol::as_str) {
+        Some("Method") => Kind::Method,
+        Some("Static") => Kind::Static,
+        Some("Class") => Kind::Class,
+        Some("Getter") => Kind::Getter,
+        Some("Setter") => Kind::Setter,
+        Some("ClassAttribute") => Kind::ClassAttribute,
+        Some(_) => return None,
+        None if field.is_some() => Kind::Getter,
+        None => return None,
+    };
+
+    Some(Member {
+        kind,
+        name: entry.strings.into_iter().next()?,
+        source: field
+            .map(Source::Field)
+            .or(source)
+            .unwrap_or(Source::Generated),
+    })
+}
+
+/// What a body of PyO3's code mentions (including in the items inside it).
+struct Mentions<'tcx> {
+    tcx: TyCtxt<'tcx>,
+    typeck: Option<&'tcx TypeckResults<'tcx>>,
+    /// Its string literals (C strings too), in order.
+    strings: Vec<String>,
+    /// The items its paths resolve to, and the types it's generic over.
+    defs: Vec<DefId>,
+    /// The fields it takes the offset of (`offset_of!(T, field)`).
+    fields: Vec<Symbol>,
+    /// The entries of each `PyClassItems` table in it,
+    /// noting whether they're `slots`.
+    tables: Vec<(bool, Mentions<'tcx>)>,
+}
+
+impl<'tcx> Mentions<'tcx> {
+    fn new(tcx: TyCtxt<'tcx>, typeck: Option<&'tcx TypeckResults<'tcx>>) -> Self {
+        Self {
+            tcx,
+            typeck,
+            strings: vec![],
+            defs: vec![],
+            fields: vec![],
+            tables: vec![],
+        }
+    }
+
+    /// Everything mentioned in the body of `did`.
+    fn of(tcx: TyCtxt<'tcx>, did: LocalDefId) -> Self {
+        let mut mentions = Self::new(tcx, None);
+        if let Some(body) = tcx.hir_maybe_body_owned_by(did) {
+            mentions.visit_nested_body(body.id());
+        }
+
+        mentions
+    }
+}
+
+impl<'tcx> Visitor<'tcx> for Mentions<'tcx> {
+    type NestedFilter = nested_filter::All;
+
+    fn maybe_tcx(&mut self) -> Self::MaybeTyCtxt {
+        self.tcx
+    }
+
+    fn visit_nested_body(&mut self, id: hir::BodyId) {
+        let typeck = self.typeck.replace(self.tcx.typeck_body(id));
+        intravisit::walk_body(self, self.tcx.hir_body(id));
+        self.typeck = typeck;
+    }
+
+    fn visit_qpath(&mut self, qpath: &'tcx hir::QPath<'tcx>, id: hir::HirId, _: rustc_span::Span) {
+        if let Some(typeck) = self.typeck {
+            if let Some(did) = typeck.qpath_res(qpath, id).opt_def_id() {
+                self.defs.push(did);
+            }
+        }
+
+        intravisit::walk_qpath(self, qpath, id);
+    }
+
+    fn visit_expr(&mut self, expr: &'tcx hir::Expr<'tcx>) {
+        match &expr.kind {
+            hir::ExprKind::Lit(lit) => match lit.node {
+                rustc_ast::LitKind::Str(string, _) => self.strings.push(string.to_string()),
+                rustc_ast::LitKind::CStr(ref bytes, _) => {
+                    let bytes = bytes.strip_suffix(&[0]).unwrap_or(bytes);
+                    self.strings
+                        .push(String::from_utf8_lossy(bytes).into_owned());
+                }
+                _ => (),
+            },
+            hir::ExprKind::OffsetOf(_, fields) => {
+                self.fields.extend(fields.iter().map(|field| field.name));
+            }
+            // `add_class::<T>()`, and the like.
+            hir::ExprKind::MethodCall(..) => {
+                if let Some(typeck) = self.typeck {
+                    let args = typeck.node_args(expr.hir_id);
+                    let adts = args.types().filter_map(|ty| ty.ty_adt_def());
+                    self.defs.extend(adts.map(|adt| adt.did()));
+                }
+            }
+            // `PyClassItems { methods: &[...], slots: &[...] }`
+            hir::ExprKind::Struct(_, fields, _) => {
+                for field in fields.iter() {
+                    let slot = match field.ident.as_str() {
+                        "methods" => false,
+                        "slots" => true,
+                        _ => continue,
+                    };
+                    let hir::ExprKind::AddrOf(_, _, entries) = field.expr.kind else {
+                        continue;
+                    };
+                    let hir::ExprKind::Array(entries) = entries.kind else {
+                        continue;
+                    };
+
+                    for entry in entries {
+                        let mut mentions = Mentions::new(self.tcx, self.typeck);
+                        mentions.visit_expr(entry);
+                        self.tables.push((slot, mentions));
+                    }
+                }
+            }
+            _ => (),
+        }
+
+        intravisit::walk_expr(self, expr);
+    }
+}
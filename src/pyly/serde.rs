@@ -0,0 +1,463 @@
+//!
+//! Exposed structs and enums as [serde](https://serde.rs) writes them (with `--serde`):
+//! records of the keys they're serialized with, rather than classes of their fields.
+//!
+
+use rustc_ast::MetaItemInner;
+use rustc_hir::def::CtorKind;
+use rustc_hir::def_id::DefId;
+use rustc_middle::ty::{EarlyBinder, GenericArgs, GenericArgsRef, Ty, TyCtxt, TyKind, VariantDef};
+use rustc_span::Symbol;
+
+use crate::pyly::{
+    comptime::exposed::PyLyCtx,
+    docs::docs,
+    items, simplify_ty,
+    stubs::{Key, Literal, Stubs},
+    PyLy,
+};
+
+/// What an item's `#[serde(...)]` attributes say about how it's serialized.
+#[derive(Debug, Default)]
+struct Serde {
+    /// `rename = "..."` (or its `serialize` name).
+    rename: Option<String>,
+    /// `rename_all = "..."`, for a container's fields (or an enum's variants).
+    rename_all: Option<String>,
+    /// `rename_all_fields = "..."`, for the fields of each of an enum's variants.
+    rename_all_fields: Option<String>,
+    /// `tag = "..."`
+    tag: Option<String>,
+    /// `content = "..."`
+    content: Option<String>,
+    untagged: bool,
+    transparent: bool,
+    flatten: bool,
+    /// `skip` or `skip_serializing`
+    skip: bool,
+    /// `default` or `skip_serializing_if`: it may be left out.
+    default: bool,
+}
+
+impl Serde {
+    fn of(tcx: TyCtxt<'_>, did: DefId) -> Self {
+        let mut serde = Self::default();
+
+        let items = tcx
+            .get_attrs(did, Symbol::intern("serde"))
+            .flat_map(|attr| attr.meta_item_list().unwrap_or_default());
+
+        for item in items {
+            match item.name_or_empty().as_str() {
+                "rename" => serde.rename = serialized(&item).or(serde.rename),
+                "rename_all" => serde.rename_all = serialized(&item).or(serde.rename_all),
+                "rename_all_fields" => {
+                    serde.rename_all_fields = serialized(&item).or(serde.rename_all_fields)
+                }
+                "tag" => serde.tag = serialized(&item),
+                "content" => serde.content = serialized(&item),
+                "untagged" => serde.untagged = true,
+                "transparent" => serde.transparent = true,
+                "flatten" => serde.flatten = true,
+                "skip" | "skip_serializing" => serde.skip = true,
+                "default" | "skip_serializing_if" => serde.default = true,
+                _ => (),
+            }
+        }
+
+        serde
+    }
+}
+
+/// The string in `key = "..."`, or its `serialize` one in `key(serialize = "...")`.
+fn serialized(item: &MetaItemInner) -> Option<String> {
+    if let Some(value) = item.value_str() {
+        return Some(value.to_string());
+    }
+
+    item.meta_item_list()?
+        .iter()
+        .find(|item| item.has_name(Symbol::intern("serialize")))
+        .and_then(MetaItemInner::value_str)
+        .map(|value| value.to_string())
+}
+
+/// Does this struct or enum implement serde's `Serialize` (or `Deserialize`)?
+pub fn is_serialized(tcx: TyCtxt<'_>, did: DefId) -> bool {
+    let is_serde = |trait_: DefId| {
+        matches!(
+            tcx.crate_name(trait_.krate).as_str(),
+            "serde" | "serde_core"
+        ) && matches!(tcx.item_name(trait_).as_str(), "Serialize" | "Deserialize")
+    };
+
+    tcx.all_local_trait_impls(())
+        .iter()
+        .filter(|(&trait_, _)| is_serde(trait_))
+        .flat_map(|(_, impls)| impls)
+        .any(|&impl_| {
+            tcx.type_of(impl_)
+                .instantiate_identity()
+                .ty_adt_def()
+                .is_some_and(|adt| adt.did() == did)
+        })
+}
+
+/// Declare an exposed struct or enum as the records (and literals) serde writes it as.
+pub fn declare<S: Stubs>(
+    tcx: TyCtxt<'_>,
+    pyly: &PyLy,
+    ctx: &mut PyLyCtx,
+    stubs: &mut S,
+    did: DefId,
+) {
+    let adt = tcx.adt_def(did);
+    let serde = Serde::of(tcx, did);
+    let name = items::name(tcx, did);
+    let doc = docs(tcx, pyly, stubs, did);
+    let generics = items::generics(tcx, did);
+    let args = GenericArgs::identity_for_item(tcx, did);
+
+    // A struct with named fields is a record of them (under its `tag`, if it has one),
+    // and any other is what its fields are serialized as.
+    if adt.is_struct() {
+        let variant = adt.non_enum_variant();
+        if variant.ctor_kind().is_some() || serde.transparent {
+            let value = content(tcx, pyly, ctx, stubs, did, variant, args);
+            stubs.alias(&name, doc, &generics, value);
+            return;
+        }
+
+        let tag = serde.tag.as_ref().map(|tag| {
+            let name = serde.rename.clone();
+            tagged(
+                stubs,
+                tag,
+                &name.unwrap_or_else(|| tcx.item_name(did).to_string()),
+            )
+        });
+
+        let rename_all = serde.rename_all.as_deref();
+        let fields = keys(
+            tcx,
+            pyly,
+            ctx,
+            stubs,
+            did,
+            variant,
+            args,
+            rename_all,
+            serde.default,
+        );
+        stubs.record(
+            &name,
+            doc,
+            &generics,
+            tag.into_iter().chain(fields).collect(),
+        );
+        return;
+    }
+
+    // Otherwise, it's the union of how each of its variants is tagged.
+    let variants = adt
+        .variants()
+        .iter()
+        .filter_map(|var| variant(tcx, pyly, ctx, stubs, did, &serde, var))
+        .collect();
+
+    let union = stubs.union(variants);
+    stubs.alias(&name, doc, &generics, union);
+}
+
+/// How a `variant` of `adt` is serialized, tagged as its `serde` says,
+/// declaring a record (`{Enum}{Variant}`) for it if it needs one.
+/// Skipped variants aren't serialized at all.
+fn variant<S: Stubs>(
+    tcx: TyCtxt<'_>,
+    pyly: &PyLy,
+    ctx: &mut PyLyCtx,
+    stubs: &mut S,
+    adt: DefId,
+    serde: &Serde,
+    variant: &VariantDef,
+) -> Option<S::Expr> {
+    let own = Serde::of(tcx, variant.def_id);
+    if own.skip {
+        return None;
+    }
+
+    let tag = own.rename.unwrap_or_else(|| match &serde.rename_all {
+        Some(rule) => rename_variant(rule, variant.name.as_str()),
+        None => variant.name.to_string(),
+    });
+    let rename_all = own
+        .rename_all
+        .as_deref()
+        .or(serde.rename_all_fields.as_deref());
+    let record = format!("{}{}", items::name(tcx, adt), variant.name);
+    let doc = docs(tcx, pyly, stubs, variant.def_id);
+    let args = GenericArgs::identity_for_item(tcx, adt);
+
+    let value = match (&serde.tag, &serde.content, variant.ctor_kind()) {
+        // Untagged: just the variant's own content.
+        (_, _, None) if serde.untagged => {
+            let keys = keys(tcx, pyly, ctx, stubs, adt, variant, args, rename_all, false);
+            declare_record(tcx, pyly, ctx, stubs, adt, &record, doc, keys)
+        }
+        (_, _, Some(_)) if serde.untagged => content(tcx, pyly, ctx, stubs, adt, variant, args),
+        // Adjacently tagged: `{"tag": "Variant", "content": ...}`
+        (Some(tag_key), Some(content_key), ctor) => {
+            let content = match ctor {
+                Some(CtorKind::Const) => None,
+                Some(CtorKind::Fn) => Some(content(tcx, pyly, ctx, stubs, adt, variant, args)),
+                None => {
+                    let keys = keys(tcx, pyly, ctx, stubs, adt, variant, args, rename_all, false);
+                    let name = format!("{record}Content");
+                    Some(declare_record(
+                        tcx, pyly, ctx, stubs, adt, &name, None, keys,
+                    ))
+                }
+            };
+
+            let mut keys = vec![tagged(stubs, tag_key, &tag)];
+            keys.extend(content.map(|annotation| Key {
+                name: content_key.clone(),
+                annotation,
+                doc: None,
+                required: true,
+            }));
+            declare_record(tcx, pyly, ctx, stubs, adt, &record, doc, keys)
+        }
+        // Internally tagged: `{"tag": "Variant", ...}`,
+        // with the variant's own keys (or those of the struct it holds).
+        (Some(tag_key), None, ctor) => {
+            let mut keys = vec![tagged(stubs, tag_key, &tag)];
+            match ctor {
+                None => keys.extend(self::keys(
+                    tcx, pyly, ctx, stubs, adt, variant, args, rename_all, false,
+                )),
+                Some(CtorKind::Fn) if variant.fields.len() == 1 => {
+                    let field = variant.fields.iter().next().expect("a newtype's field");
+                    let ty = simplify_ty(tcx, adt, EarlyBinder::bind(field.ty(tcx, args)));
+                    keys.extend(flattened(tcx, pyly, ctx, stubs, adt, ty));
+                }
+                Some(_) => (),
+            }
+
+            declare_record(tcx, pyly, ctx, stubs, adt, &record, doc, keys)
+        }
+        // Externally tagged: `"Variant"`, or `{"Variant": ...}`.
+        (None, _, Some(CtorKind::Const)) => stubs.literal(Literal::Str(tag)),
+        (None, _, Some(CtorKind::Fn)) => {
+            let content = content(tcx, pyly, ctx, stubs, adt, variant, args);
+            stubs.keyed(&tag, content)
+        }
+        (None, _, None) => {
+            let keys = keys(tcx, pyly, ctx, stubs, adt, variant, args, rename_all, false);
+            let record = declare_record(tcx, pyly, ctx, stubs, adt, &record, doc, keys);
+            stubs.keyed(&tag, record)
+        }
+    };
+
+    Some(value)
+}
+
+/// Declare a record for (a variant of) `adt`, generic over the same parameters,
+/// and refer to it.
+#[allow(clippy::too_many_arguments)]
+fn declare_record<S: Stubs>(
+    tcx: TyCtxt<'_>,
+    pyly: &PyLy,
+    ctx: &mut PyLyCtx,
+    stubs: &mut S,
+    adt: DefId,
+    name: &str,
+    doc: Option<String>,
+    keys: Vec<Key<S::Expr>>,
+) -> S::Expr {
+    stubs.record(name, doc, &items::generics(tcx, adt), keys);
+
+    let args = GenericArgs::identity_for_item(tcx, adt);
+    let args = items::type_args(tcx, pyly, ctx, stubs, adt, args);
+    stubs.reference(&items::module(tcx, adt), name, args)
+}
+
+/// The key a `tag` has, always exactly `value`.
+fn tagged<S: Stubs>(stubs: &mut S, tag: &str, value: &str) -> Key<S::Expr> {
+    Key {
+        name: tag.to_string(),
+        annotation: stubs.literal(Literal::Str(value.to_string())),
+        doc: None,
+        required: true,
+    }
+}
+
+/// What a tuple-like (or unit, or `transparent`) `variant` of `parent`
+/// is serialized as: its one field, `null` for none at all,
+/// or otherwise an array of them.
+fn content<'tcx, S: Stubs>(
+    tcx: TyCtxt<'tcx>,
+    pyly: &PyLy,
+    ctx: &mut PyLyCtx,
+    stubs: &mut S,
+    parent: DefId,
+    variant: &VariantDef,
+    args: GenericArgsRef<'tcx>,
+) -> S::Expr {
+    let tys = variant
+        .fields
+        .iter()
+        .filter(|field| !Serde::of(tcx, field.did).skip)
+        .map(|field| simplify_ty(tcx, parent, EarlyBinder::bind(field.ty(tcx, args))))
+        .collect::<Vec<_>>();
+
+    let ty = match tys.as_slice() {
+        &[ty] => ty,
+        tys => Ty::new_tup(tcx, tys),
+    };
+    items::annotation(tcx, pyly, ctx, stubs, parent, ty)
+}
+
+/// A key for each of `variant`'s named fields (with `args` for its type's parameters),
+/// except those skipped by serde (or `#[pyly(skip)]`), used within `parent`.
+/// Flattened fields have their own keys in their place.
+///
+/// Fields are renamed with `rename_all`, unless they have their own `rename`,
+/// and they're all left out when their value is the `default`.
+#[allow(clippy::too_many_arguments)]
+fn keys<'tcx, S: Stubs>(
+    tcx: TyCtxt<'tcx>,
+    pyly: &PyLy,
+    ctx: &mut PyLyCtx,
+    stubs: &mut S,
+    parent: DefId,
+    variant: &VariantDef,
+    args: GenericArgsRef<'tcx>,
+    rename_all: Option<&str>,
+    default: bool,
+) -> Vec<Key<S::Expr>> {
+    let mut keys = vec![];
+
+    for field in &variant.fields {
+        let serde = Serde::of(tcx, field.did);
+        if serde.skip || items::is_skipped(tcx, field.did) {
+            continue;
+        }
+
+        let ty = simplify_ty(tcx, parent, EarlyBinder::bind(field.ty(tcx, args)));
+        if serde.flatten {
+            keys.extend(flattened(tcx, pyly, ctx, stubs, parent, ty));
+            continue;
+        }
+
+        let name = serde.rename.unwrap_or_else(|| match rename_all {
+            Some(rule) => rename_field(rule, field.name.as_str()),
+            None => field.name.as_str().to_string(),
+        });
+
+        keys.push(Key {
+            name,
+            annotation: items::annotation(tcx, pyly, ctx, stubs, parent, ty),
+            doc: docs(tcx, pyly, stubs, field.did),
+            required: !(serde.default || default),
+        });
+    }
+
+    keys
+}
+
+/// The keys of a `ty` flattened into another record, used within `parent`.
+///
+/// Only this crate's own structs (with named fields) have keys known ahead of time,
+/// so anything else (say, a map) adds none.
+fn flattened<'tcx, S: Stubs>(
+    tcx: TyCtxt<'tcx>,
+    pyly: &PyLy,
+    ctx: &mut PyLyCtx,
+    stubs: &mut S,
+    parent: DefId,
+    ty: Ty<'tcx>,
+) -> Vec<Key<S::Expr>> {
+    let TyKind::Adt(adt, args) = ty.kind() else {
+        return vec![];
+    };
+
+    if !adt.did().is_local() || !adt.is_struct() || adt.non_enum_variant().ctor_kind().is_some() {
+        return vec![];
+    }
+
+    let serde = Serde::of(tcx, adt.did());
+    let rename_all = serde.rename_all.as_deref();
+    let variant = adt.non_enum_variant();
+    keys(
+        tcx,
+        pyly,
+        ctx,
+        stubs,
+        parent,
+        variant,
+        args,
+        rename_all,
+        serde.default,
+    )
+}
+
+/// A variant's name, as serde's `rename_all = "{rule}"` has it.
+fn rename_variant(rule: &str, variant: &str) -> String {
+    match rule {
+        "lowercase" => variant.to_ascii_lowercase(),
+        "UPPERCASE" => variant.to_ascii_uppercase(),
+        "camelCase" => lower_first(variant),
+        "snake_case" => {
+            let mut snake = String::new();
+            for (i, ch) in variant.char_indices() {
+                if i > 0 && ch.is_uppercase() {
+                    snake.push('_');
+                }
+                snake.push(ch.to_ascii_lowercase());
+            }
+            snake
+        }
+        "SCREAMING_SNAKE_CASE" => rename_variant("snake_case", variant).to_ascii_uppercase(),
+        "kebab-case" => rename_variant("snake_case", variant).replace('_', "-"),
+        "SCREAMING-KEBAB-CASE" => rename_variant("SCREAMING_SNAKE_CASE", variant).replace('_', "-"),
+        // `PascalCase`, as variants already are.
+        _ => variant.to_string(),
+    }
+}
+
+/// A field's name, as serde's `rename_all = "{rule}"` has it.
+fn rename_field(rule: &str, field: &str) -> String {
+    match rule {
+        "UPPERCASE" | "SCREAMING_SNAKE_CASE" => field.to_ascii_uppercase(),
+        "PascalCase" => {
+            let mut pascal = String::new();
+            let mut capitalize = true;
+            for ch in field.chars() {
+                match ch {
+                    '_' => capitalize = true,
+                    _ if capitalize => {
+                        pascal.push(ch.to_ascii_uppercase());
+                        capitalize = false;
+                    }
+                    _ => pascal.push(ch),
+                }
+            }
+            pascal
+        }
+        "camelCase" => lower_first(&rename_field("PascalCase", field)),
+        "kebab-case" => field.replace('_', "-"),
+        "SCREAMING-KEBAB-CASE" => field.to_ascii_uppercase().replace('_', "-"),
+        // `lowercase` and `snake_case`, as fields already are.
+        _ => field.to_string(),
+    }
+}
+
+/// `name`, with its first letter in lowercase.
+fn lower_first(name: &str) -> String {
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(first) => first.to_ascii_lowercase().to_string() + chars.as_str(),
+        None => String::new(),
+    }
+}
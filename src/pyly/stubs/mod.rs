@@ -0,0 +1,259 @@
+//!
+//! Assembling and rendering the stubs for each supported language.
+//!
+
+pub mod python;
+pub mod typescript;
+
+use std::{
+    fmt, fs, io,
+    path::{Path, PathBuf},
+};
+
+use rustpython_ast::bigint::BigInt;
+
+use crate::pyly::{comptime::exposed::LangType, error::PyLyError};
+
+/// A stub module for one language, built up one exposed item at a time.
+pub trait Stubs {
+    /// The language's types, as evaluated from [pyly_lib::Exposed::AS].
+    type Type: LangType;
+    /// A type annotation within the stubs.
+    type Expr: Clone;
+
+    /// File extension of the stubs, without the leading dot.
+    const EXTENSION: &str;
+    /// File name (without its extension) of a package's own stubs.
+    const PACKAGE: &str;
+
+    /// Declare `name` as an alias of the type `value`,
+    /// generic over the type parameters `generics`.
+    fn alias(&mut self, name: &str, doc: Option<String>, generics: &[String], value: Self::Expr);
+
+    /// Declare a class (or the like) with the given (annotated) attributes,
+    /// and methods, generic over the type parameters `generics`,
+    /// which inherits everything the classes `bases` have.
+    fn class(
+        &mut self,
+        name: &str,
+        doc: Option<String>,
+        generics: &[String],
+        bases: Vec<Self::Expr>,
+        attrs: Vec<Attr<Self::Expr>>,
+        methods: Vec<Function<Self::Expr>>,
+    );
+
+    /// Declare a protocol (or the like), which other types can implement,
+    /// with the given (annotated) attributes, and methods,
+    /// generic over the type parameters `generics`.
+    fn protocol(
+        &mut self,
+        name: &str,
+        doc: Option<String>,
+        generics: &[String],
+        attrs: Vec<Attr<Self::Expr>>,
+        methods: Vec<Function<Self::Expr>>,
+    );
+
+    /// Declare an enumeration, with the given members' values, and methods.
+    ///
+    /// `int` is set when the values are spelled out in Rust.
+    fn enumeration(
+        &mut self,
+        name: &str,
+        doc: Option<String>,
+        int: bool,
+        members: Vec<Member>,
+        methods: Vec<Function<Self::Expr>>,
+    );
+
+    /// Declare a free function.
+    fn function(&mut self, function: Function<Self::Expr>);
+
+    /// Declare a record of the given (annotated) keys, such as an object
+    /// is serialized as, generic over the type parameters `generics`.
+    fn record(
+        &mut self,
+        name: &str,
+        doc: Option<String>,
+        generics: &[String],
+        keys: Vec<Key<Self::Expr>>,
+    );
+
+    /// Declare a constant, of the type `annotation`.
+    fn constant(&mut self, name: &str, doc: Option<String>, annotation: Self::Expr);
+
+    /// Exactly `value`.
+    fn literal(&mut self, value: Literal) -> Self::Expr;
+
+    /// An object with just the one `key`, of type `value`.
+    fn keyed(&mut self, key: &str, value: Self::Expr) -> Self::Expr;
+
+    /// The union of all of `types`.
+    fn union(&mut self, types: Vec<Self::Expr>) -> Self::Expr;
+
+    /// Refer to another item declared in the stubs for `module`
+    /// (this one, or another), with any type arguments it takes.
+    fn reference(&mut self, module: &str, name: &str, args: Vec<Self::Expr>) -> Self::Expr;
+
+    /// Render an intra-doc link to another exposed item.
+    fn link(&mut self, link: Link) -> String;
+
+    /// Re-export `name`, declared in the stubs for `module`, from these ones as `alias`
+    /// (for a `pub use`).
+    fn reexport(&mut self, module: &str, name: &str, alias: &str);
+
+    /// Convert a language's type into its annotation,
+    /// recording any imports it needs.
+    fn annotation(&mut self, ty: &Self::Type) -> Self::Expr;
+
+    /// Render the finished stubs as source: `package` is set
+    /// when they're written as a package's own (e.g. `__init__.pyi`).
+    ///
+    /// Fails if they hold something the language's writer doesn't know how to write.
+    fn render(self, package: bool) -> Result<String, fmt::Error>;
+}
+
+/// Where each crate's Rust modules go in the stubs: under its root package,
+/// which is the crate's own name unless renamed (in its `Cargo.toml`).
+#[derive(Debug, Clone)]
+pub struct Layout {
+    krate: String,
+    root: String,
+}
+
+impl Layout {
+    pub fn new(krate: &str, root: Option<&str>) -> Self {
+        Self {
+            krate: krate.to_string(),
+            root: root.unwrap_or(krate).to_string(),
+        }
+    }
+
+    /// The (dotted) module for a Rust module path (e.g. `mycrate.geometry`
+    /// for `mycrate::geometry`), with this crate under its root package.
+    ///
+    /// Modules that are already dotted are left as they are.
+    pub fn module(&self, path: &str) -> String {
+        let mut segments = path.split("::");
+        let first = match segments.next() {
+            Some(krate) if krate == self.krate => self.root.as_str(),
+            first => first.unwrap_or_default(),
+        };
+
+        [first]
+            .into_iter()
+            .chain(segments)
+            .collect::<Vec<_>>()
+            .join(".")
+    }
+}
+
+/// An attribute of a class, with its type already annotated.
+#[derive(Debug, Clone)]
+pub struct Attr<E> {
+    pub name: String,
+    pub annotation: E,
+    pub doc: Option<String>,
+    /// Can it only be read, not set?
+    pub readonly: bool,
+}
+
+/// A key of a record, with its type already annotated.
+#[derive(Debug, Clone)]
+pub struct Key<E> {
+    pub name: String,
+    pub annotation: E,
+    pub doc: Option<String>,
+    /// Is it always there, or can it be left out?
+    pub required: bool,
+}
+
+/// A member of an enumeration.
+#[derive(Debug, Clone)]
+pub struct Member {
+    pub name: String,
+    pub value: BigInt,
+    pub doc: Option<String>,
+}
+
+/// A value that's a type of its own (e.g. `typing.Literal[1]`).
+#[derive(Debug, Clone)]
+pub enum Literal {
+    Int(BigInt),
+    Str(String),
+    Bool(bool),
+}
+
+/// A function's signature, with its types already annotated.
+#[derive(Debug, Clone)]
+pub struct Function<E> {
+    pub name: String,
+    pub doc: Option<String>,
+    /// Its own type parameters (not those of its `impl`).
+    pub generics: Vec<String>,
+    /// Does it take `self` (in any form)?
+    pub receiver: bool,
+    /// Every other parameter.
+    pub params: Vec<(String, E)>,
+    /// What it returns, unless that's `()`.
+    pub returns: Option<E>,
+    /// What it may raise instead, if it returns a `Result`.
+    pub raises: Option<E>,
+}
+
+/// An intra-doc link to another exposed item, as declared in the stubs.
+#[derive(Debug, Clone)]
+pub struct Link {
+    /// The (dotted) module it's declared in.
+    pub module: String,
+    /// Its path within that module (e.g. `Page.get`).
+    pub path: String,
+    pub kind: LinkKind,
+    /// The link's text, unless it's just the item's (Rust) path.
+    pub text: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LinkKind {
+    /// A class (or protocol, or alias).
+    Class,
+    /// A free function.
+    Function,
+    /// A method of a class (or protocol).
+    Method,
+    /// An attribute of a class (or member of an enumeration).
+    Attribute,
+}
+
+/// Write the stubs for the (dotted) `module` to `{out_dir}/{module}.{S::EXTENSION}`,
+/// with each of its parent modules as a directory (e.g. `geometry/shapes.pyi`).
+///
+/// A `package`'s own stubs go in its directory instead (e.g. `geometry/__init__.pyi`).
+pub fn write<S: Stubs>(
+    out_dir: &Path,
+    module: &str,
+    package: bool,
+    stubs: S,
+) -> Result<PathBuf, PyLyError> {
+    let mut path = out_dir.join(module.replace('.', "/"));
+    if package {
+        path.push(S::PACKAGE);
+    }
+    let path = path.with_extension(S::EXTENSION);
+
+    let written = fs::create_dir_all(path.parent().unwrap_or(out_dir)).and_then(|()| {
+        let source = stubs.render(package).map_err(|_| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                "the stubs hold something that can't be written",
+            )
+        })?;
+        fs::write(&path, source)
+    });
+
+    match written {
+        Ok(()) => Ok(path),
+        Err(error) => Err(PyLyError::Write { path, error }),
+    }
+}
@@ -0,0 +1,1099 @@
+//!
+//! Assembling and rendering Python stub (`.pyi`) modules.
+//!
+
+use std::{
+    collections::BTreeSet,
+    fmt::{self, Write},
+};
+
+use pyly_lib::python;
+use rustpython_ast::{self as ast, text_size::TextRange};
+use serde::{Deserialize, Serialize};
+
+use super::{Attr, Function, Key, Layout, Link, LinkKind, Literal, Member, Stubs};
+
+/// Choices in how the stubs are written.
+#[derive(Debug, Default, Clone, Copy, clap::Args, Serialize, Deserialize)]
+pub struct StubStyle {
+    /// How to write optional types.
+    #[arg(long, value_enum, default_value_t)]
+    pub optional: OptionalStyle,
+
+    /// How to write what a function returning a `Result` may raise.
+    #[arg(long, value_enum, default_value_t)]
+    pub result: ResultStyle,
+}
+
+#[derive(Debug, Default, Clone, Copy, clap::ValueEnum, Serialize, Deserialize)]
+pub enum OptionalStyle {
+    /// `T | None`
+    #[default]
+    Union,
+    /// `typing.Optional[T]`
+    Typing,
+}
+
+#[derive(Debug, Default, Clone, Copy, clap::ValueEnum, Serialize, Deserialize)]
+pub enum ResultStyle {
+    /// `T`, with `E` under `Raises:` in the docstring
+    #[default]
+    Raises,
+    /// `T | E`
+    Union,
+}
+
+/// A Python stub module, built up one exposed item at a time.
+#[derive(Debug)]
+pub struct StubModule {
+    /// The (dotted) module these are the stubs for.
+    module: String,
+    layout: Layout,
+    style: StubStyle,
+    /// Modules which need to be `import`ed by the stub.
+    imports: BTreeSet<String>,
+    /// Items `from` other modules, `import`ed `as` part of this one.
+    reexports: BTreeSet<(String, String, String)>,
+    /// `typing.TypeVar`s already declared in the stub.
+    type_vars: BTreeSet<String>,
+    body: Vec<ast::Stmt>,
+}
+
+impl StubModule {
+    pub fn new(module: &str, layout: Layout, style: StubStyle) -> Self {
+        Self {
+            module: layout.module(module),
+            layout,
+            style,
+            imports: Default::default(),
+            reexports: Default::default(),
+            type_vars: Default::default(),
+            body: vec![],
+        }
+    }
+
+    /// `typing.{attr}`
+    fn typing(&mut self, attr: &str) -> ast::Expr {
+        self.import("typing");
+        expr::attribute(expr::name("typing"), attr)
+    }
+
+    /// A type variable, declared (just the once) where it's first used:
+    /// ```py
+    /// T = typing.TypeVar("T")
+    /// ```
+    fn type_var(&mut self, name: &str) -> ast::Expr {
+        if self.type_vars.insert(name.to_string()) {
+            let type_var = self.typing("TypeVar");
+            let value = expr::call(
+                type_var,
+                vec![expr::constant(ast::Constant::Str(name.to_string()))],
+            );
+
+            self.body.push(ast::Stmt::Assign(ast::StmtAssign {
+                range: TextRange::default(),
+                targets: vec![expr::name(name)],
+                value: Box::new(value),
+                type_comment: None,
+            }));
+        }
+
+        expr::name(name)
+    }
+
+    /// A function (or method) definition, noting what it raises
+    /// unless that's already part of what it returns.
+    fn def(&self, mut function: Function<ast::Expr>, method: bool) -> ast::Stmt {
+        if let ResultStyle::Union = self.style.result {
+            function.raises = None;
+        }
+
+        stmt::def(function, method)
+    }
+
+    /// ```py
+    /// class name(extends, typing.{base}, typing.{last}[T]):
+    ///     """doc"""
+    ///     attr: annotation
+    ///     """doc"""
+    ///     def method(self) -> returns: ...
+    /// ```
+    ///
+    /// With no bases (then, no generics), it's just `class name:`.
+    /// Read-only attributes are `@property`s instead.
+    #[allow(clippy::too_many_arguments)]
+    fn class_def(
+        &mut self,
+        name: &str,
+        doc: Option<String>,
+        extends: Vec<ast::Expr>,
+        bases: &[&str],
+        generics: &[String],
+        attrs: Vec<Attr<ast::Expr>>,
+        methods: Vec<Function<ast::Expr>>,
+    ) {
+        let mut bases = bases
+            .iter()
+            .map(|base| self.typing(base))
+            .collect::<Vec<_>>();
+        if let Some(last) = bases.pop() {
+            bases.push(match generics.is_empty() {
+                true => last,
+                false => {
+                    let generics = generics.iter().map(|t| self.type_var(t)).collect();
+                    expr::subscript(last, generics)
+                }
+            });
+        }
+        let bases = extends.into_iter().chain(bases).collect::<Vec<_>>();
+
+        for t in methods.iter().flat_map(|method| &method.generics) {
+            self.type_var(t);
+        }
+
+        let attrs = attrs.into_iter().flat_map(|attr| {
+            if attr.readonly {
+                return vec![stmt::property(attr.name, attr.annotation, attr.doc)];
+            }
+
+            let assign = ast::Stmt::AnnAssign(ast::StmtAnnAssign {
+                range: TextRange::default(),
+                target: Box::new(expr::name(&attr.name)),
+                annotation: Box::new(attr.annotation),
+                value: None,
+                simple: true,
+            });
+
+            [assign]
+                .into_iter()
+                .chain(attr.doc.map(stmt::docstring))
+                .collect()
+        });
+
+        let mut body = doc
+            .map(stmt::docstring)
+            .into_iter()
+            .chain(attrs)
+            .chain(methods.into_iter().map(|method| self.def(method, true)))
+            .collect::<Vec<_>>();
+
+        if body.is_empty() {
+            body.push(stmt::ellipsis());
+        }
+
+        self.body.push(ast::Stmt::ClassDef(ast::StmtClassDef {
+            range: TextRange::default(),
+            name: name.into(),
+            bases,
+            keywords: vec![],
+            body,
+            decorator_list: vec![],
+            type_params: vec![],
+        }));
+    }
+
+    fn import(&mut self, module: &str) {
+        self.imports.insert(module.to_string());
+    }
+
+    /// Assemble the final module, with its imports first,
+    /// then its re-exports.
+    pub fn finish(self) -> ast::ModModule {
+        let imports = self.imports.into_iter().map(|module| {
+            ast::Stmt::Import(ast::StmtImport {
+                range: TextRange::default(),
+                names: vec![ast::Alias {
+                    range: TextRange::default(),
+                    name: module.into(),
+                    asname: None,
+                }],
+            })
+        });
+
+        // Stubs only re-export what's imported `as` itself (or another name).
+        let reexports = self.reexports.into_iter().map(|(module, name, alias)| {
+            ast::Stmt::ImportFrom(ast::StmtImportFrom {
+                range: TextRange::default(),
+                module: Some(module.into()),
+                names: vec![ast::Alias {
+                    range: TextRange::default(),
+                    name: name.into(),
+                    asname: Some(alias.into()),
+                }],
+                level: None,
+            })
+        });
+
+        ast::ModModule {
+            range: Default::default(),
+            body: imports.chain(reexports).chain(self.body).collect(),
+            type_ignores: vec![],
+        }
+    }
+}
+
+impl Stubs for StubModule {
+    type Type = python::Type<'static>;
+    type Expr = ast::Expr;
+
+    const EXTENSION: &str = "pyi";
+    const PACKAGE: &str = "__init__";
+
+    /// Declare `name` as an alias of the type `value`:
+    /// ```py
+    /// name: typing.TypeAlias = value
+    /// """doc"""
+    /// ```
+    ///
+    /// Any type variables in `value` make the alias generic.
+    fn alias(&mut self, name: &str, doc: Option<String>, generics: &[String], value: ast::Expr) {
+        for t in generics {
+            self.type_var(t);
+        }
+        let annotation = self.typing("TypeAlias");
+
+        self.body.push(ast::Stmt::AnnAssign(ast::StmtAnnAssign {
+            range: TextRange::default(),
+            target: Box::new(expr::name(name)),
+            annotation: Box::new(annotation),
+            value: Some(Box::new(value)),
+            simple: true,
+        }));
+        self.body.extend(doc.map(stmt::docstring));
+    }
+
+    /// Declare a class with the given (annotated) attributes, and methods:
+    /// ```py
+    /// class name(base, typing.Generic[T]):
+    ///     attr: annotation
+    ///     def method(self) -> returns: ...
+    /// ```
+    fn class(
+        &mut self,
+        name: &str,
+        doc: Option<String>,
+        generics: &[String],
+        bases: Vec<ast::Expr>,
+        attrs: Vec<Attr<ast::Expr>>,
+        methods: Vec<Function<ast::Expr>>,
+    ) {
+        let typing: &[&str] = match generics.is_empty() {
+            true => &[],
+            false => &["Generic"],
+        };
+        self.class_def(name, doc, bases, typing, generics, attrs, methods);
+    }
+
+    /// Declare a protocol with the given (annotated) attributes, and methods:
+    /// ```py
+    /// class name(typing.Protocol[T]):
+    ///     attr: annotation
+    ///     def method(self) -> returns: ...
+    /// ```
+    fn protocol(
+        &mut self,
+        name: &str,
+        doc: Option<String>,
+        generics: &[String],
+        attrs: Vec<Attr<ast::Expr>>,
+        methods: Vec<Function<ast::Expr>>,
+    ) {
+        self.class_def(name, doc, vec![], &["Protocol"], generics, attrs, methods);
+    }
+
+    /// Declare an enumeration, with the given members' values, and methods:
+    /// ```py
+    /// class name(enum.Enum):
+    ///     member = value
+    ///     def method(self) -> returns: ...
+    /// ```
+    fn enumeration(
+        &mut self,
+        name: &str,
+        doc: Option<String>,
+        int: bool,
+        members: Vec<Member>,
+        methods: Vec<Function<ast::Expr>>,
+    ) {
+        self.import("enum");
+        let base = expr::attribute(expr::name("enum"), if int { "IntEnum" } else { "Enum" });
+
+        let members = members.into_iter().flat_map(|member| {
+            let assign = ast::Stmt::Assign(ast::StmtAssign {
+                range: TextRange::default(),
+                targets: vec![expr::name(&member.name)],
+                value: Box::new(expr::constant(ast::Constant::Int(member.value))),
+                type_comment: None,
+            });
+
+            [assign].into_iter().chain(member.doc.map(stmt::docstring))
+        });
+
+        let mut body = doc
+            .map(stmt::docstring)
+            .into_iter()
+            .chain(members)
+            .chain(methods.into_iter().map(|method| self.def(method, true)))
+            .collect::<Vec<_>>();
+
+        if body.is_empty() {
+            body.push(stmt::ellipsis());
+        }
+
+        self.body.push(ast::Stmt::ClassDef(ast::StmtClassDef {
+            range: TextRange::default(),
+            name: name.into(),
+            bases: vec![base],
+            keywords: vec![],
+            body,
+            decorator_list: vec![],
+            type_params: vec![],
+        }));
+    }
+
+    /// ```py
+    /// def name(param: annotation) -> returns: ...
+    /// ```
+    fn function(&mut self, function: Function<ast::Expr>) {
+        for t in &function.generics {
+            self.type_var(t);
+        }
+        let def = self.def(function, false);
+        self.body.push(def);
+    }
+
+    /// Declare a record of keys, as a `typing.TypedDict`:
+    /// ```py
+    /// class name(typing.TypedDict, typing.Generic[T]):
+    ///     """doc"""
+    ///     key: annotation
+    ///     """doc"""
+    ///     optional: typing.NotRequired[annotation]
+    /// ```
+    ///
+    /// Keys which can't be attributes (e.g. `'first-name'`) need `TypedDict`'s functional syntax,
+    /// which can't be generic, nor document its keys:
+    /// ```py
+    /// name = typing.TypedDict('name', {'first-name': annotation})
+    /// """doc"""
+    /// ```
+    fn record(
+        &mut self,
+        name: &str,
+        doc: Option<String>,
+        generics: &[String],
+        keys: Vec<Key<ast::Expr>>,
+    ) {
+        let keys = keys
+            .into_iter()
+            .map(|key| match key.required {
+                true => key,
+                false => Key {
+                    annotation: expr::subscript(self.typing("NotRequired"), vec![key.annotation]),
+                    ..key
+                },
+            })
+            .collect::<Vec<_>>();
+
+        if keys.iter().all(|key| is_identifier(&key.name)) {
+            let attrs = keys
+                .into_iter()
+                .map(|key| Attr {
+                    name: key.name,
+                    annotation: key.annotation,
+                    doc: key.doc,
+                    readonly: false,
+                })
+                .collect();
+
+            let bases: &[&str] = match generics.is_empty() {
+                true => &["TypedDict"],
+                false => &["TypedDict", "Generic"],
+            };
+            self.class_def(name, doc, vec![], bases, generics, attrs, vec![]);
+            return;
+        }
+
+        let str = |value: String| expr::constant(ast::Constant::Str(value));
+        let (names, annotations) = keys
+            .into_iter()
+            .map(|key| (Some(str(key.name)), key.annotation))
+            .unzip();
+        let keys = ast::Expr::Dict(ast::ExprDict {
+            range: TextRange::default(),
+            keys: names,
+            values: annotations,
+        });
+
+        let typed_dict = self.typing("TypedDict");
+        self.body.push(ast::Stmt::Assign(ast::StmtAssign {
+            range: TextRange::default(),
+            targets: vec![expr::name(name)],
+            value: Box::new(expr::call(typed_dict, vec![str(name.to_string()), keys])),
+            type_comment: None,
+        }));
+        self.body.extend(doc.map(stmt::docstring));
+    }
+
+    /// Declare a constant, which can't be reassigned:
+    /// ```py
+    /// name: typing.Final[annotation]
+    /// """doc"""
+    /// ```
+    fn constant(&mut self, name: &str, doc: Option<String>, annotation: ast::Expr) {
+        let annotation = expr::subscript(self.typing("Final"), vec![annotation]);
+
+        self.body.push(ast::Stmt::AnnAssign(ast::StmtAnnAssign {
+            range: TextRange::default(),
+            target: Box::new(expr::name(name)),
+            annotation: Box::new(annotation),
+            value: None,
+            simple: true,
+        }));
+        self.body.extend(doc.map(stmt::docstring));
+    }
+
+    /// `typing.Literal[value]`
+    fn literal(&mut self, value: Literal) -> ast::Expr {
+        let value = expr::constant(match value {
+            Literal::Int(int) => ast::Constant::Int(int),
+            Literal::Str(str) => ast::Constant::Str(str),
+            Literal::Bool(bool) => ast::Constant::Bool(bool),
+        });
+        expr::subscript(self.typing("Literal"), vec![value])
+    }
+
+    /// `dict[typing.Literal['key'], value]`, as near as Python gets
+    /// to a dict of just the one key.
+    fn keyed(&mut self, key: &str, value: ast::Expr) -> ast::Expr {
+        let key = self.literal(Literal::Str(key.to_string()));
+        expr::subscript(expr::name("dict"), vec![key, value])
+    }
+
+    /// `A | B | ...`, or `typing.Never` for no types at all.
+    ///
+    /// Literals are gathered into the first (`typing.Literal['a', 'b']`).
+    fn union(&mut self, mut types: Vec<ast::Expr>) -> ast::Expr {
+        if types.is_empty() {
+            return self.typing("Never");
+        }
+
+        let mut literals = vec![];
+        types.retain(|ty| match literal_values(ty) {
+            Some(values) if !literals.is_empty() => {
+                literals.extend(values);
+                false
+            }
+            Some(values) => {
+                literals.extend(values);
+                true
+            }
+            None => true,
+        });
+        if literals.len() > 1 {
+            if let Some(first) = types.iter_mut().find(|ty| literal_values(ty).is_some()) {
+                *first = expr::subscript(self.typing("Literal"), literals);
+            }
+        }
+
+        let first = types.remove(0);
+        types.into_iter().fold(first, |left, right| {
+            ast::Expr::BinOp(ast::ExprBinOp {
+                range: TextRange::default(),
+                left: Box::new(left),
+                op: ast::Operator::BitOr,
+                right: Box::new(right),
+            })
+        })
+    }
+
+    /// Refer to another item declared in the stubs: `name[A, B, ...]`,
+    /// or `module.name[A, B, ...]` from another module.
+    ///
+    /// Stubs can refer to names declared later on in them,
+    /// so items from this module need no quoting.
+    /// Rust module paths (e.g. from `module_path!()`) are laid out like the stubs.
+    fn reference(&mut self, module: &str, name: &str, args: Vec<ast::Expr>) -> ast::Expr {
+        let module = self.layout.module(module);
+        let item = match module == self.module {
+            true => expr::name(name),
+            false => {
+                self.import(&module);
+                expr::attribute(expr::name(&module), name)
+            }
+        };
+
+        match args.is_empty() {
+            true => item,
+            false => expr::subscript(item, args),
+        }
+    }
+
+    /// A Sphinx cross-reference (e.g. ``:meth:`Page.get` ``),
+    /// qualified by its module if it's declared in another.
+    fn link(&mut self, link: Link) -> String {
+        let role = match link.kind {
+            LinkKind::Class => "class",
+            LinkKind::Function => "func",
+            LinkKind::Method => "meth",
+            LinkKind::Attribute => "attr",
+        };
+
+        let module = self.layout.module(&link.module);
+        let target = match module == self.module {
+            true => link.path,
+            false => format!("{module}.{}", link.path),
+        };
+
+        // Roles are already set in code, so can't hold any of their own.
+        match link.text {
+            Some(text) => format!(":{role}:`{} <{target}>`", text.replace('`', "")),
+            None => format!(":{role}:`{target}`"),
+        }
+    }
+
+    /// ```py
+    /// from module import name as alias
+    /// ```
+    fn reexport(&mut self, module: &str, name: &str, alias: &str) {
+        let module = self.layout.module(module);
+        self.reexports
+            .insert((module, name.to_string(), alias.to_string()));
+    }
+
+    /// Convert a [python::Type] into its type annotation,
+    /// recording any imports it needs.
+    fn annotation(&mut self, ty: &python::Type<'static>) -> ast::Expr {
+        use python::{InBuilt, Literal, Type, Typing};
+
+        match ty {
+            Type::InBuilt(in_built) => match in_built {
+                InBuilt::None => expr::constant(ast::Constant::None),
+                InBuilt::Ellipses => expr::constant(ast::Constant::Ellipsis),
+                InBuilt::Int => expr::name("int"),
+                InBuilt::Float => expr::name("float"),
+                InBuilt::Complex => expr::name("complex"),
+                InBuilt::Bool => expr::name("bool"),
+                InBuilt::Str => expr::name("str"),
+                InBuilt::Bytes => expr::name("bytes"),
+                InBuilt::ByteArray => expr::name("bytearray"),
+                InBuilt::Tuple(items) => {
+                    let items = items.iter().map(|t| self.annotation(t)).collect();
+                    expr::subscript(expr::name("tuple"), items)
+                }
+                InBuilt::List(t) => {
+                    let t = self.annotation(t);
+                    expr::subscript(expr::name("list"), vec![t])
+                }
+                InBuilt::Set(t) => {
+                    let t = self.annotation(t);
+                    expr::subscript(expr::name("set"), vec![t])
+                }
+                InBuilt::Dict(kv) => {
+                    let kv = kv.iter().map(|t| self.annotation(t)).collect();
+                    expr::subscript(expr::name("dict"), kv)
+                }
+            },
+            Type::Typing(typing) => match typing {
+                Typing::Iterator(t) => {
+                    let t = self.annotation(t);
+                    expr::subscript(self.typing("Iterator"), vec![t])
+                }
+                Typing::Any => self.typing("Any"),
+                Typing::Optional(t) => {
+                    let t = self.annotation(t);
+                    match self.style.optional {
+                        OptionalStyle::Union => {
+                            let none = expr::constant(ast::Constant::None);
+                            self.union(vec![t, none])
+                        }
+                        OptionalStyle::Typing => expr::subscript(self.typing("Optional"), vec![t]),
+                    }
+                }
+                Typing::Literal(values) => {
+                    let values = values
+                        .iter()
+                        .map(|value| {
+                            expr::constant(match *value {
+                                Literal::Int(int) => ast::Constant::Int(int.into()),
+                                Literal::Str(str) => ast::Constant::Str(str.to_string()),
+                                Literal::Bool(bool) => ast::Constant::Bool(bool),
+                            })
+                        })
+                        .collect();
+                    expr::subscript(self.typing("Literal"), values)
+                }
+                Typing::Callable { params, returns } => {
+                    let params = params.iter().map(|t| self.annotation(t)).collect();
+                    let returns = self.annotation(returns);
+                    expr::subscript(self.typing("Callable"), vec![expr::list(params), returns])
+                }
+            },
+            Type::Union(items) => {
+                let members = Type::union_members(items)
+                    .into_iter()
+                    .map(|t| self.annotation(t))
+                    .collect();
+                self.union(members)
+            }
+            // A trait's own `Self`.
+            Type::TypeVar("Self") => self.typing("Self"),
+            Type::TypeVar(name) => self.type_var(name),
+            Type::Raises { returns, raises } => {
+                let returns = self.annotation(returns);
+                match self.style.result {
+                    ResultStyle::Raises => returns,
+                    ResultStyle::Union => {
+                        let raises = self.annotation(raises);
+                        self.union(vec![returns, raises])
+                    }
+                }
+            }
+            Type::Custom { module, name } => self.reference(module, name, vec![]),
+            Type::Class { module, name, args } => {
+                let args = args.iter().map(|t| self.annotation(t)).collect();
+                self.reference(module, name, args)
+            }
+        }
+    }
+
+    fn render(self, _: bool) -> Result<String, fmt::Error> {
+        render(&self.finish())
+    }
+}
+
+/// Render a stub module as Python source,
+/// or fail if it has a statement stubs don't need (say, a `for` loop).
+pub fn render(module: &ast::ModModule) -> Result<String, fmt::Error> {
+    let mut out = String::new();
+    let mut previous: Option<&ast::Stmt> = None;
+
+    for stmt in &module.body {
+        // Leave a gap between the imports and everything else,
+        // and around classes.
+        let gap = previous.is_some_and(|p| {
+            (is_import(p) && !is_import(stmt)) || p.is_class_def_stmt() || stmt.is_class_def_stmt()
+        });
+
+        if gap {
+            out.push('\n');
+        }
+
+        write_stmt(&mut out, stmt, 0)?;
+        previous = Some(stmt);
+    }
+
+    Ok(out)
+}
+
+fn write_stmt(f: &mut impl Write, stmt: &ast::Stmt, indent: usize) -> fmt::Result {
+    write!(f, "{:indent$}", "", indent = indent * 4)?;
+
+    match stmt {
+        ast::Stmt::Import(ast::StmtImport { names, .. }) => {
+            write!(f, "import ")?;
+            write_aliases(f, names)?;
+        }
+        ast::Stmt::ImportFrom(ast::StmtImportFrom { module, names, .. }) => {
+            let module = module.as_ref().map_or("", |m| m.as_str());
+            write!(f, "from {module} import ")?;
+            write_aliases(f, names)?;
+        }
+        ast::Stmt::AnnAssign(ast::StmtAnnAssign {
+            target,
+            annotation,
+            value,
+            ..
+        }) => {
+            write!(f, "{target}: {annotation}")?;
+            if let Some(value) = value {
+                write!(f, " = {value}")?;
+            }
+        }
+        ast::Stmt::Assign(ast::StmtAssign { targets, value, .. }) => {
+            for target in targets {
+                write!(f, "{target} = ")?;
+            }
+            write!(f, "{value}")?;
+        }
+        ast::Stmt::Expr(ast::StmtExpr { value, .. }) => match value.as_ref() {
+            ast::Expr::Constant(ast::ExprConstant {
+                value: ast::Constant::Str(doc),
+                ..
+            }) => write_docstring(f, doc, indent)?,
+            value => write!(f, "{value}")?,
+        },
+        ast::Stmt::FunctionDef(ast::StmtFunctionDef {
+            name,
+            args,
+            body,
+            decorator_list,
+            returns,
+            ..
+        }) => {
+            for decorator in decorator_list {
+                write!(f, "@{decorator}\n{:indent$}", "", indent = indent * 4)?;
+            }
+
+            write!(f, "def {name}(")?;
+            for (i, ast::ArgWithDefault { def, .. }) in args.args.iter().enumerate() {
+                if i > 0 {
+                    write!(f, ", ")?;
+                }
+
+                write!(f, "{}", def.arg)?;
+                if let Some(annotation) = &def.annotation {
+                    write!(f, ": {annotation}")?;
+                }
+            }
+            write!(f, ")")?;
+
+            if let Some(returns) = returns {
+                write!(f, " -> {returns}")?;
+            }
+
+            // Bodies of just `...` fit on one line.
+            if let [stmt] = body.as_slice() {
+                if !is_docstring(stmt) {
+                    write!(f, ": ")?;
+                    return write_stmt(f, stmt, 0);
+                }
+            }
+
+            writeln!(f, ":")?;
+            for stmt in body {
+                write_stmt(f, stmt, indent + 1)?;
+            }
+
+            return Ok(());
+        }
+        ast::Stmt::ClassDef(ast::StmtClassDef {
+            name, bases, body, ..
+        }) => {
+            write!(f, "class {name}")?;
+            if !bases.is_empty() {
+                write!(f, "(")?;
+                write_comma_separated(f, bases)?;
+                write!(f, ")")?;
+            }
+            write!(f, ":")?;
+
+            // Empty classes fit on one line: `class Name: ...`
+            if let [stmt @ ast::Stmt::Expr(ast::StmtExpr { value, .. })] = body.as_slice() {
+                if value.is_constant_expr() && !is_docstring(stmt) {
+                    return writeln!(f, " {value}");
+                }
+            }
+
+            writeln!(f)?;
+            for stmt in body {
+                write_stmt(f, stmt, indent + 1)?;
+            }
+
+            return Ok(());
+        }
+        // PyLy doesn't build any others.
+        _ => return Err(fmt::Error),
+    }
+
+    writeln!(f)
+}
+
+/// ```py
+/// """
+/// Each line of doc.
+/// """
+/// ```
+///
+/// Or, for just the one line, `"""doc"""`.
+fn write_docstring(f: &mut impl Write, doc: &str, indent: usize) -> fmt::Result {
+    let doc = doc.replace('\\', "\\\\").replace("\"\"\"", "\\\"\"\"");
+
+    if !doc.contains('\n') && !doc.ends_with('"') {
+        return write!(f, "\"\"\"{doc}\"\"\"");
+    }
+
+    writeln!(f, "\"\"\"")?;
+    for line in doc.lines() {
+        match line.is_empty() {
+            true => writeln!(f)?,
+            false => writeln!(f, "{:indent$}{line}", "", indent = indent * 4)?,
+        }
+    }
+    write!(f, "{:indent$}\"\"\"", "", indent = indent * 4)
+}
+
+/// The values of a `typing.Literal[...]`, if that's what this is.
+fn literal_values(ty: &ast::Expr) -> Option<Vec<ast::Expr>> {
+    let ast::Expr::Subscript(ast::ExprSubscript { value, slice, .. }) = ty else {
+        return None;
+    };
+    let ast::Expr::Attribute(ast::ExprAttribute {
+        value: module,
+        attr,
+        ..
+    }) = value.as_ref()
+    else {
+        return None;
+    };
+    if !matches!(module.as_ref(), ast::Expr::Name(name) if name.id.as_str() == "typing")
+        || attr.as_str() != "Literal"
+    {
+        return None;
+    }
+
+    Some(match slice.as_ref() {
+        ast::Expr::Tuple(ast::ExprTuple { elts, .. }) => elts.clone(),
+        value => vec![value.clone()],
+    })
+}
+
+/// Can this be a Python attribute's name (an identifier, but not a keyword)?
+fn is_identifier(name: &str) -> bool {
+    const KEYWORDS: [&str; 35] = [
+        "False", "None", "True", "and", "as", "assert", "async", "await", "break", "class",
+        "continue", "def", "del", "elif", "else", "except", "finally", "for", "from", "global",
+        "if", "import", "in", "is", "lambda", "nonlocal", "not", "or", "pass", "raise", "return",
+        "try", "while", "with", "yield",
+    ];
+
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|first| first == '_' || first.is_alphabetic())
+        && chars.all(|c| c == '_' || c.is_alphanumeric())
+        && !KEYWORDS.contains(&name)
+}
+
+/// Is this statement an `import` (or `from ... import`)?
+fn is_import(stmt: &ast::Stmt) -> bool {
+    stmt.is_import_stmt() || stmt.is_import_from_stmt()
+}
+
+/// Is this statement a docstring (a lone string)?
+fn is_docstring(stmt: &ast::Stmt) -> bool {
+    matches!(
+        stmt,
+        ast::Stmt::Expr(ast::StmtExpr { value, .. })
+            if matches!(value.as_ref(), ast::Expr::Constant(ast::ExprConstant {
+                value: ast::Constant::Str(_),
+                ..
+            }))
+    )
+}
+
+fn write_comma_separated(f: &mut impl Write, items: &[impl fmt::Display]) -> fmt::Result {
+    for (i, item) in items.iter().enumerate() {
+        if i > 0 {
+            write!(f, ", ")?;
+        }
+
+        write!(f, "{item}")?;
+    }
+
+    Ok(())
+}
+
+fn write_aliases(f: &mut impl Write, names: &[ast::Alias]) -> fmt::Result {
+    for (i, ast::Alias { name, asname, .. }) in names.iter().enumerate() {
+        if i > 0 {
+            write!(f, ", ")?;
+        }
+
+        write!(f, "{name}")?;
+        if let Some(asname) = asname {
+            write!(f, " as {asname}")?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Shorthands for building [ast::Stmt]s.
+mod stmt {
+    use rustpython_ast::{self as ast, text_size::TextRange};
+
+    use crate::pyly::stubs::Function;
+
+    /// ```py
+    /// def name(param: annotation) -> returns: ...
+    /// ```
+    ///
+    /// Methods either take `self`, or are `@staticmethod`s.
+    /// Its doc, and anything it raises, go in its docstring instead of `...`:
+    /// ```py
+    /// def name() -> returns:
+    ///     """
+    ///     doc
+    ///
+    ///     Raises:
+    ///         raises
+    ///     """
+    /// ```
+    pub fn def(function: Function<ast::Expr>, method: bool) -> ast::Stmt {
+        let Function {
+            name,
+            doc,
+            receiver,
+            params,
+            returns,
+            raises,
+            ..
+        } = function;
+
+        let arg = |name: &str, annotation: Option<ast::Expr>| ast::ArgWithDefault {
+            range: Default::default(),
+            def: ast::Arg {
+                range: TextRange::default(),
+                arg: name.into(),
+                annotation: annotation.map(Box::new),
+                type_comment: None,
+            },
+            default: None,
+        };
+
+        let decorator_list = match method && !receiver {
+            true => vec![super::expr::name("staticmethod")],
+            false => vec![],
+        };
+
+        let args = receiver
+            .then(|| arg("self", None))
+            .into_iter()
+            .chain(params.into_iter().map(|(name, ty)| arg(&name, Some(ty))))
+            .collect();
+
+        ast::Stmt::FunctionDef(ast::StmtFunctionDef {
+            range: TextRange::default(),
+            name: name.into(),
+            args: Box::new(ast::Arguments {
+                range: Default::default(),
+                posonlyargs: vec![],
+                args,
+                vararg: None,
+                kwonlyargs: vec![],
+                kwarg: None,
+            }),
+            body: vec![match (doc, raises) {
+                (Some(doc), Some(raises)) => docstring(format!("{doc}\n\nRaises:\n    {raises}")),
+                (None, Some(raises)) => docstring(format!("Raises:\n    {raises}")),
+                (Some(doc), None) => docstring(doc),
+                (None, None) => ellipsis(),
+            }],
+            decorator_list,
+            returns: Some(Box::new(
+                returns.unwrap_or_else(|| super::expr::constant(ast::Constant::None)),
+            )),
+            type_comment: None,
+            type_params: vec![],
+        })
+    }
+
+    /// A read-only attribute:
+    /// ```py
+    /// @property
+    /// def name(self) -> annotation: ...
+    /// ```
+    pub fn property(name: String, annotation: ast::Expr, doc: Option<String>) -> ast::Stmt {
+        let getter = Function {
+            name,
+            doc,
+            generics: vec![],
+            receiver: true,
+            params: vec![],
+            returns: Some(annotation),
+            raises: None,
+        };
+
+        let mut def = def(getter, true);
+        if let ast::Stmt::FunctionDef(def) = &mut def {
+            def.decorator_list = vec![super::expr::name("property")];
+        }
+
+        def
+    }
+
+    /// A docstring.
+    pub fn docstring(doc: String) -> ast::Stmt {
+        ast::Stmt::Expr(ast::StmtExpr {
+            range: TextRange::default(),
+            value: Box::new(super::expr::constant(ast::Constant::Str(doc))),
+        })
+    }
+
+    /// `...`, for an otherwise empty body.
+    pub fn ellipsis() -> ast::Stmt {
+        ast::Stmt::Expr(ast::StmtExpr {
+            range: TextRange::default(),
+            value: Box::new(super::expr::constant(ast::Constant::Ellipsis)),
+        })
+    }
+}
+
+/// Shorthands for building [ast::Expr]s.
+mod expr {
+    use rustpython_ast::{self as ast, text_size::TextRange};
+
+    pub fn name(id: &str) -> ast::Expr {
+        ast::Expr::Name(ast::ExprName {
+            range: TextRange::default(),
+            id: id.into(),
+            ctx: ast::ExprContext::Load,
+        })
+    }
+
+    pub fn attribute(value: ast::Expr, attr: &str) -> ast::Expr {
+        ast::Expr::Attribute(ast::ExprAttribute {
+            range: TextRange::default(),
+            value: Box::new(value),
+            attr: attr.into(),
+            ctx: ast::ExprContext::Load,
+        })
+    }
+
+    pub fn constant(value: ast::Constant) -> ast::Expr {
+        ast::Expr::Constant(ast::ExprConstant {
+            range: TextRange::default(),
+            value,
+            kind: None,
+        })
+    }
+
+    /// `func(args...)`
+    pub fn call(func: ast::Expr, args: Vec<ast::Expr>) -> ast::Expr {
+        ast::Expr::Call(ast::ExprCall {
+            range: TextRange::default(),
+            func: Box::new(func),
+            args,
+            keywords: vec![],
+        })
+    }
+
+    /// `[items...]`
+    pub fn list(items: Vec<ast::Expr>) -> ast::Expr {
+        ast::Expr::List(ast::ExprList {
+            range: TextRange::default(),
+            elts: items,
+            ctx: ast::ExprContext::Load,
+        })
+    }
+
+    /// `value[items...]`
+    pub fn subscript(value: ast::Expr, mut items: Vec<ast::Expr>) -> ast::Expr {
+        let slice = match items.len() {
+            1 => items.remove(0),
+            _ => ast::Expr::Tuple(ast::ExprTuple {
+                range: TextRange::default(),
+                elts: items,
+                ctx: ast::ExprContext::Load,
+            }),
+        };
+
+        ast::Expr::Subscript(ast::ExprSubscript {
+            range: TextRange::default(),
+            value: Box::new(value),
+            slice: Box::new(slice),
+            ctx: ast::ExprContext::Load,
+        })
+    }
+}
@@ -0,0 +1,540 @@
+//!
+//! Assembling and rendering TypeScript declaration (`.d.ts`) files.
+//!
+
+use std::{
+    collections::BTreeSet,
+    fmt::{self, Write},
+};
+
+use pyly_lib::typescript;
+
+use super::{Attr, Function, Key, Layout, Link, Literal, Member, Stubs};
+
+/// A TypeScript declaration file, built up one exposed item at a time.
+#[derive(Debug)]
+pub struct DtsModule {
+    /// The (dotted) module these are the declarations for.
+    module: String,
+    layout: Layout,
+    /// Modules whose declarations need to be `import`ed.
+    imports: BTreeSet<String>,
+    /// Declarations `from` other modules, `export`ed `as` part of this one.
+    reexports: BTreeSet<(String, String, String)>,
+    /// Each declaration, already rendered.
+    body: Vec<String>,
+}
+
+impl DtsModule {
+    pub fn new(module: &str, layout: Layout) -> Self {
+        Self {
+            module: layout.module(module),
+            layout,
+            imports: Default::default(),
+            reexports: Default::default(),
+            body: vec![],
+        }
+    }
+
+    /// Where to import `module`'s declarations from, relative to this module's
+    /// (e.g. `../geometry/shapes` from `colors.palette`),
+    /// which are a level further down for a `package`'s own (`index.d.ts`).
+    ///
+    /// Other crates' declarations are expected alongside this one's.
+    fn import_path(&self, module: &str, package: bool) -> String {
+        let up = match self.module.matches('.').count() + package as usize {
+            0 => "./".to_string(),
+            depth => "../".repeat(depth),
+        };
+
+        format!("{up}{}", module.replace('.', "/"))
+    }
+
+    fn generic(&mut self, name: &str, args: &[typescript::Type<'static>]) -> String {
+        let args = args.iter().map(|t| self.annotation(t)).collect::<Vec<_>>();
+
+        format!("{name}<{}>", args.join(", "))
+    }
+}
+
+impl Stubs for DtsModule {
+    type Type = typescript::Type<'static>;
+    type Expr = String;
+
+    const EXTENSION: &str = "d.ts";
+    const PACKAGE: &str = "index";
+
+    /// ```ts
+    /// /** doc */
+    /// export type name<T> = value;
+    /// ```
+    fn alias(&mut self, name: &str, doc: Option<String>, generics: &[String], value: String) {
+        let generics = parameters(generics);
+        self.body.push(format!(
+            "{}export type {name}{generics} = {value};\n",
+            jsdoc(doc.as_deref(), 0)
+        ));
+    }
+
+    /// ```ts
+    /// /** doc */
+    /// export interface name<T> extends base<T> {
+    ///     /** doc */
+    ///     attr: annotation;
+    ///     readonly attr: annotation;
+    ///     method(param: annotation): returns;
+    /// }
+    ///
+    /// export declare const name: {
+    ///     "new"<T>(param: annotation): name<T>;
+    /// };
+    /// ```
+    ///
+    /// Interfaces have no static members, so methods without `self`
+    /// go on a constant of the same name instead.
+    fn class(
+        &mut self,
+        name: &str,
+        doc: Option<String>,
+        generics: &[String],
+        bases: Vec<String>,
+        attrs: Vec<Attr<String>>,
+        methods: Vec<Function<String>>,
+    ) {
+        let (methods, statics): (Vec<_>, Vec<_>) =
+            methods.into_iter().partition(|method| method.receiver);
+
+        let mut decl = jsdoc(doc.as_deref(), 0);
+        write!(decl, "export interface {name}{}", parameters(generics))
+            .expect("writing to a String");
+        if !bases.is_empty() {
+            write!(decl, " extends {}", bases.join(", ")).expect("writing to a String");
+        }
+        decl.push_str(" {");
+        if !attrs.is_empty() || !methods.is_empty() {
+            decl.push('\n');
+        }
+
+        for attr in attrs {
+            decl.push_str(&jsdoc(attr.doc.as_deref(), 1));
+            let readonly = if attr.readonly { "readonly " } else { "" };
+            writeln!(decl, "    {readonly}{}: {};", attr.name, attr.annotation)
+                .expect("writing to a String");
+        }
+
+        for method in methods {
+            decl.push_str(&jsdoc(function_doc(&method).as_deref(), 1));
+            writeln!(decl, "    {};", signature(method)).expect("writing to a String");
+        }
+
+        decl.push_str("}\n");
+        self.body.push(decl);
+
+        if statics.is_empty() {
+            return;
+        }
+
+        // Static methods are generic over the class's own parameters too,
+        // and `this` (a trait's `Self`) is an instance of it.
+        let instance = format!("{name}{}", parameters(generics));
+        let mut decl = format!("export declare const {name}: {{\n");
+        for method in statics {
+            decl.push_str(&jsdoc(function_doc(&method).as_deref(), 1));
+            let method = Function {
+                // Otherwise, `new(...)` would be a constructor.
+                name: match method.name.as_str() {
+                    "new" => "\"new\"".to_string(),
+                    name => property(name),
+                },
+                generics: generics.iter().cloned().chain(method.generics).collect(),
+                params: method
+                    .params
+                    .into_iter()
+                    .map(|(param, annotation)| (param, this_as(&annotation, &instance)))
+                    .collect(),
+                returns: method.returns.map(|returns| this_as(&returns, &instance)),
+                ..method
+            };
+            writeln!(decl, "    {};", signature(method)).expect("writing to a String");
+        }
+
+        decl.push_str("};\n");
+        self.body.push(decl);
+    }
+
+    /// Declared just like a class: interfaces are already structural.
+    fn protocol(
+        &mut self,
+        name: &str,
+        doc: Option<String>,
+        generics: &[String],
+        attrs: Vec<Attr<String>>,
+        methods: Vec<Function<String>>,
+    ) {
+        self.class(name, doc, generics, vec![], attrs, methods);
+    }
+
+    /// ```ts
+    /// export enum name {
+    ///     member = value,
+    /// }
+    /// ```
+    ///
+    /// Values are only given if they were in Rust,
+    /// as TypeScript numbers its members the same way.
+    /// Enums can't have methods, so those are left out.
+    fn enumeration(
+        &mut self,
+        name: &str,
+        doc: Option<String>,
+        int: bool,
+        members: Vec<Member>,
+        _: Vec<Function<String>>,
+    ) {
+        let mut decl = jsdoc(doc.as_deref(), 0);
+        write!(decl, "export enum {name} {{").expect("writing to a String");
+        if !members.is_empty() {
+            decl.push('\n');
+        }
+
+        for Member {
+            name: member,
+            value,
+            doc,
+        } in members
+        {
+            decl.push_str(&jsdoc(doc.as_deref(), 1));
+            match int {
+                true => writeln!(decl, "    {member} = {value},"),
+                false => writeln!(decl, "    {member},"),
+            }
+            .expect("writing to a String");
+        }
+
+        decl.push_str("}\n");
+        self.body.push(decl);
+    }
+
+    /// ```ts
+    /// /** doc */
+    /// export function name(param: annotation): returns;
+    /// ```
+    fn function(&mut self, function: Function<String>) {
+        let doc = jsdoc(function_doc(&function).as_deref(), 0);
+        self.body
+            .push(format!("{doc}export function {};\n", signature(function)));
+    }
+
+    /// ```ts
+    /// /** doc */
+    /// export interface name<T> {
+    ///     /** doc */
+    ///     key: annotation;
+    ///     optional?: annotation;
+    ///     "odd-key": annotation;
+    /// }
+    /// ```
+    fn record(
+        &mut self,
+        name: &str,
+        doc: Option<String>,
+        generics: &[String],
+        keys: Vec<Key<String>>,
+    ) {
+        let mut decl = jsdoc(doc.as_deref(), 0);
+        write!(decl, "export interface {name}{} {{", parameters(generics))
+            .expect("writing to a String");
+        if !keys.is_empty() {
+            decl.push('\n');
+        }
+
+        for key in keys {
+            decl.push_str(&jsdoc(key.doc.as_deref(), 1));
+            let optional = if key.required { "" } else { "?" };
+            writeln!(
+                decl,
+                "    {}{optional}: {};",
+                property(&key.name),
+                key.annotation
+            )
+            .expect("writing to a String");
+        }
+
+        decl.push_str("}\n");
+        self.body.push(decl);
+    }
+
+    /// ```ts
+    /// /** doc */
+    /// export declare const name: annotation;
+    /// ```
+    fn constant(&mut self, name: &str, doc: Option<String>, annotation: String) {
+        self.body.push(format!(
+            "{}export declare const {name}: {annotation};\n",
+            jsdoc(doc.as_deref(), 0)
+        ));
+    }
+
+    /// `1`, `"value"` or `true`
+    fn literal(&mut self, value: Literal) -> String {
+        match value {
+            Literal::Int(int) => int.to_string(),
+            Literal::Str(str) => format!("{str:?}"),
+            Literal::Bool(bool) => bool.to_string(),
+        }
+    }
+
+    /// `{ key: value }`
+    fn keyed(&mut self, key: &str, value: String) -> String {
+        format!("{{ {}: {value} }}", property(key))
+    }
+
+    /// `A | B | ...`, or `never` for no types at all.
+    ///
+    /// Repeats are left out.
+    fn union(&mut self, mut types: Vec<String>) -> String {
+        let mut seen = BTreeSet::new();
+        types.retain(|ty| seen.insert(ty.clone()));
+
+        match types.is_empty() {
+            true => "never".to_string(),
+            false => types.join(" | "),
+        }
+    }
+
+    /// `name<A, B, ...>`, or `module.name<A, B, ...>` from another module.
+    fn reference(&mut self, module: &str, name: &str, args: Vec<String>) -> String {
+        let module = self.layout.module(module);
+        let item = match module == self.module {
+            true => name.to_string(),
+            false => {
+                let item = format!("{}.{name}", namespace(&module));
+                self.imports.insert(module);
+                item
+            }
+        };
+
+        match args.is_empty() {
+            true => item,
+            false => format!("{item}<{}>", args.join(", ")),
+        }
+    }
+
+    /// `{@link name}`, or `{@link module.name | text}`,
+    /// importing its module if it's another one.
+    fn link(&mut self, link: Link) -> String {
+        let module = self.layout.module(&link.module);
+        let target = match module == self.module {
+            true => link.path,
+            false => {
+                let target = format!("{}.{}", namespace(&module), link.path);
+                self.imports.insert(module);
+                target
+            }
+        };
+
+        match link.text {
+            Some(text) => format!("{{@link {target} | {text}}}"),
+            None => format!("{{@link {target}}}"),
+        }
+    }
+
+    /// ```ts
+    /// export { name as alias } from "module";
+    /// ```
+    fn reexport(&mut self, module: &str, name: &str, alias: &str) {
+        let module = self.layout.module(module);
+        self.reexports
+            .insert((module, name.to_string(), alias.to_string()));
+    }
+
+    fn annotation(&mut self, ty: &typescript::Type<'static>) -> String {
+        use typescript::Type;
+
+        match ty {
+            // A trait's own `Self`.
+            Type::Parameter("Self") => "this".to_string(),
+            Type::Null
+            | Type::Boolean
+            | Type::Number
+            | Type::BigInt
+            | Type::String
+            | Type::Unknown
+            | Type::Parameter(_) => ty.to_string(),
+            Type::Array(t) => self.generic("Array", std::slice::from_ref(t)),
+            Type::Tuple(items) => {
+                let items = items.iter().map(|t| self.annotation(t)).collect::<Vec<_>>();
+
+                format!("[{}]", items.join(", "))
+            }
+            Type::Record(kv) => self.generic("Record", kv.as_slice()),
+            Type::Set(t) => self.generic("Set", std::slice::from_ref(t)),
+            Type::Map(kv) => self.generic("Map", kv.as_slice()),
+            Type::Iterator(t) => self.generic("Iterator", std::slice::from_ref(t)),
+            Type::Union(items) => {
+                let members = Type::union_members(items)
+                    .into_iter()
+                    .map(|t| self.annotation(t))
+                    .collect();
+                self.union(members)
+            }
+            // What's thrown goes in the function's `@throws` instead.
+            Type::Throws { returns, .. } => self.annotation(returns),
+            // A global, such as `RangeError`.
+            Type::Interface { module: "", name } => name.to_string(),
+            Type::Interface { module, name } => self.reference(module, name, vec![]),
+            Type::Class { module, name, args } => {
+                let args = args.iter().map(|t| self.annotation(t)).collect();
+                self.reference(module, name, args)
+            }
+        }
+    }
+
+    /// Imports and re-exports first, then each declaration,
+    /// with a gap around interfaces and enums.
+    fn render(self, package: bool) -> Result<String, fmt::Error> {
+        let mut out = String::new();
+
+        for module in &self.imports {
+            let path = self.import_path(module, package);
+            writeln!(
+                out,
+                "import type * as {} from \"{path}\";",
+                namespace(module)
+            )?;
+        }
+
+        for (module, name, alias) in &self.reexports {
+            let path = self.import_path(module, package);
+            match name == alias {
+                true => writeln!(out, "export {{ {name} }} from \"{path}\";"),
+                false => writeln!(out, "export {{ {name} as {alias} }} from \"{path}\";"),
+            }?;
+        }
+
+        let mut previous: Option<&str> = None;
+        for decl in &self.body {
+            let gap = match previous {
+                Some(p) => is_block(p) || is_block(decl),
+                None => !out.is_empty(),
+            };
+
+            if gap {
+                out.push('\n');
+            }
+
+            out.push_str(decl);
+            previous = Some(decl);
+        }
+
+        Ok(out)
+    }
+}
+
+/// The name another module's declarations are imported as
+/// (e.g. `geometry_shapes` for `geometry.shapes`).
+fn namespace(module: &str) -> String {
+    module.replace('.', "_")
+}
+
+/// `annotation`, with each `this` in it as `instance` instead.
+fn this_as(annotation: &str, instance: &str) -> String {
+    let is_ident = |c: char| c == '_' || c == '$' || c.is_alphanumeric();
+
+    annotation
+        .split_inclusive(|c: char| !is_ident(c))
+        .map(
+            |piece| match piece.trim_end_matches(|c: char| !is_ident(c)) {
+                "this" => piece.replacen("this", instance, 1),
+                _ => piece.to_string(),
+            },
+        )
+        .collect()
+}
+
+/// A property's name, quoted unless it's an identifier.
+fn property(name: &str) -> String {
+    let mut chars = name.chars();
+    let identifier = chars
+        .next()
+        .is_some_and(|first| first == '_' || first == '$' || first.is_alphabetic())
+        && chars.all(|c| c == '_' || c == '$' || c.is_alphanumeric());
+
+    match identifier {
+        true => name.to_string(),
+        false => format!("{name:?}"),
+    }
+}
+
+/// `<T, U, ...>`, if there are any type parameters at all.
+fn parameters(generics: &[String]) -> String {
+    match generics.is_empty() {
+        true => String::new(),
+        false => format!("<{}>", generics.join(", ")),
+    }
+}
+
+/// `name<T>(param: annotation): returns`
+fn signature(function: Function<String>) -> String {
+    let params = function
+        .params
+        .into_iter()
+        .map(|(name, annotation)| format!("{name}: {annotation}"))
+        .collect::<Vec<_>>();
+
+    format!(
+        "{}{}({}): {}",
+        function.name,
+        parameters(&function.generics),
+        params.join(", "),
+        function.returns.as_deref().unwrap_or("void")
+    )
+}
+
+/// A function's doc, noting what it may throw (if it returns a `Result`)
+/// with a `@throws` tag.
+fn function_doc(function: &Function<String>) -> Option<String> {
+    match (&function.doc, &function.raises) {
+        (Some(doc), Some(throws)) => Some(format!("{doc}\n\n@throws {{{throws}}}")),
+        (None, Some(throws)) => Some(format!("@throws {{{throws}}}")),
+        (doc, None) => doc.clone(),
+    }
+}
+
+/// A JSDoc comment for `doc`, if there is one, indented by `indent` levels:
+/// ```ts
+/// /**
+///  * Each line of doc.
+///  */
+/// ```
+///
+/// Or, for just the one line, `/** doc */`.
+fn jsdoc(doc: Option<&str>, indent: usize) -> String {
+    let Some(doc) = doc else {
+        return String::new();
+    };
+
+    let doc = doc.replace("*/", "*\\/");
+    let indent = " ".repeat(indent * 4);
+
+    if !doc.contains('\n') {
+        return format!("{indent}/** {doc} */\n");
+    }
+
+    let mut out = format!("{indent}/**\n");
+    for line in doc.lines() {
+        match line.is_empty() {
+            true => writeln!(out, "{indent} *"),
+            false => writeln!(out, "{indent} * {line}"),
+        }
+        .expect("writing to a String");
+    }
+    writeln!(out, "{indent} */").expect("writing to a String");
+
+    out
+}
+
+/// Does this declaration have a body (`{ ... }`)?
+fn is_block(decl: &str) -> bool {
+    decl.ends_with("}\n") || decl.ends_with("};\n")
+}
@@ -0,0 +1,12 @@
+//!
+//! An [pyly::Exposed::AS] which fails to evaluate,
+//! which the plugin should report as a normal compiler error.
+//!
+
+use pyly::{Exposed, Python as Py};
+
+pub struct Unfinished;
+
+impl Exposed<Py> for Unfinished {
+    const AS: <Py as pyly::Language>::Type = panic!("not exposed yet");
+}
@@ -0,0 +1,13 @@
+//!
+//! Misspelt `#[pyly::expose]` (and field) options,
+//! which the macro should point out.
+//!
+
+#![feature(register_tool)]
+#![register_tool(__pyly)]
+
+#[pyly::expose(nmae = "Renamed")]
+pub struct Misspelt {
+    #[pyly(skp)]
+    field: u8,
+}
@@ -0,0 +1,118 @@
+//!
+//! A Python module made with PyO3 alone,
+//! whose stubs should match what it has at runtime.
+//!
+
+use pyo3::{prelude::*, types::PyType};
+
+// The plugin only looks at crates which use `pyly_lib`.
+use pyly as _;
+
+/// A book, for sale.
+#[pyclass(name = "Volume")]
+pub struct Book {
+    /// What it's called.
+    #[pyo3(get, set)]
+    title: String,
+    #[pyo3(get, name = "pages")]
+    page_count: u32,
+    #[pyo3(set)]
+    price: f64,
+    isbn: String,
+}
+
+#[pymethods]
+impl Book {
+    #[new]
+    fn new(title: String, page_count: u32) -> Self {
+        Self {
+            title,
+            page_count,
+            price: 0.0,
+            isbn: String::new(),
+        }
+    }
+
+    /// Its ISBN, if it has one.
+    #[getter]
+    fn isbn(&self) -> Option<&str> {
+        (!self.isbn.is_empty()).then_some(&self.isbn)
+    }
+
+    #[setter]
+    fn set_isbn(&mut self, isbn: String) {
+        self.isbn = isbn;
+    }
+
+    #[getter]
+    fn cost(&self) -> f64 {
+        self.price
+    }
+
+    #[classattr]
+    fn shelf() -> u32 {
+        3
+    }
+
+    /// How many pages are left, from `page`.
+    #[pyo3(name = "remaining")]
+    fn pages_left(&self, page: u32) -> PyResult<u32> {
+        Ok(self.page_count.saturating_sub(page))
+    }
+
+    fn twin(slf: PyRef<'_, Self>, py: Python<'_>) -> PyResult<Py<Book>> {
+        let book = Book {
+            title: slf.title.clone(),
+            page_count: slf.page_count,
+            price: slf.price,
+            isbn: slf.isbn.clone(),
+        };
+        Py::new(py, book)
+    }
+
+    #[staticmethod]
+    fn blank() -> Self {
+        Self::new(String::new(), 0)
+    }
+
+    #[classmethod]
+    fn titled(_cls: &Bound<'_, PyType>, title: String) -> Self {
+        Self::new(title, 0)
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Volume({:?})", self.title)
+    }
+}
+
+/// The total of every book's pages.
+#[pyfunction(name = "total_pages")]
+fn total(books: Vec<u32>) -> u32 {
+    books.iter().sum()
+}
+
+#[pyfunction]
+fn shout(py: Python<'_>, text: &str) -> PyResult<String> {
+    let _ = py;
+    Ok(text.to_uppercase())
+}
+
+// What a `#[pyfunction]` from another PyO3 might expand to: without the `_PYO3_DEF`
+// the plugin reads, it's left out of the stubs (with a warning).
+macro_rules! pyfunction {
+    (fn $name:ident() {}) => {
+        #[allow(dead_code)]
+        fn $name() {}
+        mod $name {}
+    };
+}
+
+pyfunction! { fn unreadable() {} }
+
+#[pymodule]
+fn bookshop(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<Book>()?;
+    m.add_function(wrap_pyfunction!(total, m)?)?;
+    m.add_function(wrap_pyfunction!(shout, m)?)?;
+    Ok(())
+}
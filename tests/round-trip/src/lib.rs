@@ -0,0 +1,159 @@
+//!
+//! Every kind of [pyly::python::Type] (and [pyly::typescript::Type]),
+//! to check it survives the trip through `const` evaluation in the plugin.
+//!
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use pyly::{
+    python::{self, InBuilt, Type, Typing},
+    Exposed, Python as Py,
+};
+
+macro_rules! exposed {
+    ($lang: ty; $($name: ident => $as: expr),* $(,)?) => {
+        $(
+            pub struct $name;
+
+            impl Exposed<$lang> for $name {
+                const AS: <$lang as pyly::Language>::Type = $as;
+            }
+        )*
+    };
+}
+
+// python::InBuilt
+exposed! {
+    Py;
+    Nothing => Type::InBuilt(InBuilt::None),
+    Ellipses => Type::InBuilt(InBuilt::Ellipses),
+    Int => Type::InBuilt(InBuilt::Int),
+    Float => Type::InBuilt(InBuilt::Float),
+    Complex => Type::InBuilt(InBuilt::Complex),
+    Bool => Type::InBuilt(InBuilt::Bool),
+    Str => Type::InBuilt(InBuilt::Str),
+    Bytes => Type::InBuilt(InBuilt::Bytes),
+    ByteArray => Type::InBuilt(InBuilt::ByteArray),
+    Tuple => Type::InBuilt(InBuilt::Tuple(&[
+        Type::InBuilt(InBuilt::Int),
+        Type::InBuilt(InBuilt::Str),
+    ])),
+    List => Type::InBuilt(InBuilt::List(&Type::InBuilt(InBuilt::Float))),
+    Set => Type::InBuilt(InBuilt::Set(&Type::InBuilt(InBuilt::Bytes))),
+    Dict => Type::InBuilt(InBuilt::Dict(&[
+        Type::InBuilt(InBuilt::Str),
+        Type::InBuilt(InBuilt::Bool),
+    ])),
+}
+
+// python::Typing
+exposed! {
+    Py;
+    Iterator => Type::Typing(Typing::Iterator(&Type::InBuilt(InBuilt::Int))),
+    Any => Type::Typing(Typing::Any),
+    Optional => Type::Typing(Typing::Optional(&Type::InBuilt(InBuilt::Str))),
+    Literal => Type::Typing(Typing::Literal(&[
+        python::Literal::Int(-1),
+        python::Literal::Str("it's"),
+        python::Literal::Bool(true),
+    ])),
+    Callable => Type::Typing(Typing::Callable {
+        params: &[Type::InBuilt(InBuilt::Int), Type::InBuilt(InBuilt::Str)],
+        returns: &Type::InBuilt(InBuilt::None),
+    }),
+}
+
+// python::Type
+exposed! {
+    Py;
+    Custom => Type::Custom {
+        module: module_path!(),
+        name: "Nothing",
+    },
+    ForeignCustom => Type::Custom {
+        module: "other_crate::shapes",
+        name: "Circle",
+    },
+    Class => Type::Class {
+        module: "other_crate::shapes",
+        name: "Polygon",
+        args: &[Type::InBuilt(InBuilt::Int)],
+    },
+    MovedClass => Type::Class {
+        module: "geometry.shapes",
+        name: "Point2D",
+        args: &[],
+    },
+    Union => Type::Union(&[
+        Type::InBuilt(InBuilt::Int),
+        Type::Union(&[Type::InBuilt(InBuilt::Str), Type::InBuilt(InBuilt::Int)]),
+        Type::InBuilt(InBuilt::None),
+    ]),
+    Never => Type::Union(&[]),
+    TypeVar => Type::TypeVar("T"),
+}
+
+// Nested, via the library's own impls.
+exposed! {
+    Py;
+    Nested => <Box<
+        dyn std::iter::Iterator<
+            Item = (
+                u8,
+                Vec<HashSet<String>>,
+                HashMap<(bool, char), BTreeMap<String, Box<dyn std::iter::Iterator<Item = f64>>>>,
+            ),
+        >,
+    > as Exposed<Py>>::AS,
+    FilePath => <std::path::PathBuf as Exposed<Py>>::AS,
+    Timeout => <std::sync::Arc<std::time::Duration> as Exposed<Py>>::AS,
+}
+
+pub mod typescript {
+    use std::collections::{BTreeMap, HashMap, HashSet};
+
+    use pyly::{typescript::Type, Exposed, TypeScript as Ts};
+
+    // Prefixed, so as not to shadow TypeScript's own `Array`, `Set`, ...
+    exposed! {
+        Ts;
+        TsNull => Type::Null,
+        TsBoolean => Type::Boolean,
+        TsNumber => Type::Number,
+        TsBigInt => Type::BigInt,
+        TsString => Type::String,
+        TsUnknown => Type::Unknown,
+        TsArray => Type::Array(&Type::Number),
+        TsTuple => Type::Tuple(&[Type::Number, Type::String]),
+        TsRecord => Type::Record(&[Type::String, Type::Boolean]),
+        TsSet => Type::Set(&Type::BigInt),
+        TsMap => Type::Map(&[Type::Number, Type::Unknown]),
+        TsIterator => Type::Iterator(&Type::Boolean),
+        TsUnion => Type::Union(&[Type::String, Type::Null]),
+        TsParameter => Type::Parameter("T"),
+        TsInterface => Type::Interface {
+            module: module_path!(),
+            name: "TsNull",
+        },
+        TsForeignInterface => Type::Interface {
+            module: "other_crate::shapes",
+            name: "Circle",
+        },
+    }
+
+    // Nested, via the library's own impls.
+    exposed! {
+        Ts;
+        TsNested => <Box<
+            dyn std::iter::Iterator<
+                Item = (
+                    u8,
+                    Vec<HashSet<std::string::String>>,
+                    HashMap<(bool, char), BTreeMap<u128, Option<f64>>>,
+                ),
+            >,
+        > as Exposed<Ts>>::AS,
+        TsFilePath => <std::path::PathBuf as Exposed<Ts>>::AS,
+        TsTimes => <(std::time::Duration, std::time::SystemTime) as Exposed<Ts>>::AS,
+    }
+}
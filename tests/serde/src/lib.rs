@@ -0,0 +1,124 @@
+//!
+//! Types only sent to Python as JSON, declared as serde writes them.
+//!
+
+#![feature(register_tool)]
+#![register_tool(__pyly)]
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+/// Who wrote something.
+#[pyly::expose]
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Author {
+    first_name: String,
+    #[serde(rename = "surname")]
+    last_name: String,
+    /// When they were born, if we know.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    born: Option<u16>,
+    #[serde(skip)]
+    pub cache: Vec<u8>,
+    #[serde(flatten)]
+    contact: Contact,
+    #[serde(flatten)]
+    extra: HashMap<String, String>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct Contact {
+    email: String,
+}
+
+#[pyly::expose]
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Ticket {
+    seat_number: u32,
+}
+
+#[pyly::expose]
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "kind")]
+pub struct Ping {
+    at: u64,
+}
+
+#[pyly::expose]
+#[derive(Serialize, Deserialize)]
+pub struct Isbn(String);
+
+#[pyly::expose]
+#[derive(Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Pages {
+    count: u32,
+}
+
+#[pyly::expose]
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum Format {
+    Hardback,
+    PaperBack,
+    #[serde(rename = "ebook")]
+    Digital,
+    #[serde(skip)]
+    Lost,
+}
+
+/// Something that happened.
+#[pyly::expose]
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Event {
+    Opened,
+    Moved(u32, u32),
+    Renamed(String),
+    /// Someone bought a copy.
+    Sold {
+        price: f64,
+        buyer: Author,
+    },
+}
+
+#[pyly::expose]
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "type", rename_all_fields = "camelCase")]
+pub enum Shape {
+    Circle { radius_mm: f64 },
+    Written(Ticket),
+    Nothing,
+}
+
+#[pyly::expose]
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "t", content = "c")]
+pub enum Message<T> {
+    Text(String),
+    Data { body: T },
+    Empty,
+}
+
+#[pyly::expose]
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Reference {
+    Isbn(Isbn),
+    Title { title: String },
+    Unknown,
+}
+
+/// Not serialized, so still a class.
+#[pyly::expose]
+pub struct Shelf {
+    pub books: Vec<Isbn>,
+}
+
+#[pyly::expose]
+pub fn latest(_shelf: &Shelf) -> Option<Event> {
+    None
+}
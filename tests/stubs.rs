@@ -0,0 +1,769 @@
+//!
+//! Runs the plugin over some crates, and checks the stubs it writes.
+//!
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::{Command, Output},
+};
+
+const TEST_CRATE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/examples/test-crate");
+const ROUND_TRIP: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/round-trip");
+const BAD_CONST: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/bad-const");
+const BAD_OPTION: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/bad-option");
+const WORKSPACE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/workspace");
+const PYO3: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/pyo3");
+const SERDE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/serde");
+
+/// Run `cargo pyly` on `krate`, writing into its own directory for `name`.
+fn run(krate: &str, name: &str, args: &[&str]) -> (Output, PathBuf) {
+    // Cargo only re-runs the plugin when its arguments change,
+    // so use a fresh output directory for every run.
+    let out_dir = Path::new(env!("CARGO_TARGET_TMPDIR"))
+        .join(name)
+        .join(std::process::id().to_string());
+
+    let output = Command::new(env!("CARGO_BIN_EXE_cargo-pyly"))
+        .args(["pyly", "--out-dir"])
+        .arg(&out_dir)
+        .args(args)
+        .current_dir(krate)
+        .output()
+        .expect("run cargo-pyly");
+
+    (output, out_dir)
+}
+
+/// Run `cargo pyly` on `krate`, returning the contents of the stub file `name`.
+fn pyly(krate: &str, name: &str, args: &[&str]) -> String {
+    let (output, out_dir) = run(krate, name, args);
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    fs::read_to_string(out_dir.join(name)).expect("read the stub file")
+}
+
+#[test]
+fn test_crate() {
+    let stubs = pyly(TEST_CRATE, "test_crate/__init__.pyi", &[]);
+
+    assert_eq!(
+        stubs,
+        "\
+import builtins
+import enum
+import geometry.shapes
+import typing
+from test_crate.colors import Color as Color
+from test_crate.colors import fill as paint
+
+class A:
+    _0: int
+    _1: int
+
+class Pair:
+    left: A
+    right: A
+    label: str
+    seen: set[int]
+    history: list[A]
+    nickname: str | None
+    @staticmethod
+    def new(left: A, right: A, label: str) -> Pair: ...
+    def describe(self) -> str: ...
+    def rename(self, nickname: str | None) -> None: ...
+
+class Direction(enum.Enum):
+    \"\"\"A point of the compass.\"\"\"
+    North = 0
+    \"\"\"Up, on a map.\"\"\"
+    East = 1
+    South = 2
+    West = 3
+    def opposite(self) -> Direction: ...
+
+class Level(enum.IntEnum):
+    Low = -1
+    High = 10
+
+class _Shape:
+    def area(self) -> float: ...
+
+class ShapeCircle(_Shape):
+    \"\"\"Every point :attr:`radius <ShapeCircle.radius>` away from the origin.\"\"\"
+    radius: float
+
+class ShapeLine(_Shape):
+    _0: Direction
+    _1: float
+
+class ShapePoint(_Shape): ...
+
+Shape: typing.TypeAlias = ShapeCircle | ShapeLine | ShapePoint
+T = typing.TypeVar('T')
+V = typing.TypeVar('V')
+
+class Page(typing.Generic[T]):
+    \"\"\"
+    A page of items.
+
+    Get one with :meth:`Page.get`, or make a page with :func:`page`.
+    \"\"\"
+    items: list[T]
+    number: int
+    def last(self) -> T | None: ...
+    def get(self, index: int) -> T:
+        \"\"\"
+        The item at `index`, unless that's past the end (an :class:`OutOfBounds`).
+
+        Raises:
+            builtins.IndexError
+        \"\"\"
+    def zip(self, other: Page[V]) -> Page[tuple[T, V]]: ...
+
+class Visitor(typing.Protocol[T]):
+    \"\"\"Visits each item of a :class:`Page`, until told to stop.\"\"\"
+    def visit(self, item: T) -> bool:
+        \"\"\"Whether to carry on.\"\"\"
+    @staticmethod
+    def fresh() -> typing.Self: ...
+
+class Limited(typing.Protocol):
+    LIMIT: int
+
+def distance(shape: Shape, _1: tuple[float, float]) -> float: ...
+def centre(shape: Shape) -> geometry.shapes.Point2D: ...
+def page(items: list[str], number: int) -> Page[str]: ...
+def walk(page: Page[str], visitor: Visitor[str]) -> int: ...
+PAGE_SIZE: typing.Final[typing.Literal[50]]
+\"\"\"The most items a :class:`Page` holds.\"\"\"
+GREETING: typing.Final[typing.Literal['Hello, \"world\"!']]
+STRICT: typing.Final[typing.Literal[True]]
+OFFSET: typing.Final[typing.Literal[-3]]
+ORIGIN: typing.Final[tuple[float, float]]
+OutOfBounds: typing.TypeAlias = builtins.IndexError
+Chapters: typing.TypeAlias = list[Page[str]]
+\"\"\"Every :class:`page <Page>` of a book.\"\"\"
+Stack: typing.TypeAlias = list[T]
+Svelte: typing.TypeAlias = tuple[None, int, float, bool, str, tuple[bytes, set[int], dict[str, int]]]
+"
+    );
+}
+
+/// Every [pyly_lib::python::Type] should read back as the same type.
+#[test]
+fn round_trip() {
+    let stubs = pyly(ROUND_TRIP, "round_trip.pyi", &[]);
+
+    assert_eq!(
+        stubs,
+        "\
+import datetime
+import geometry.shapes
+import os
+import other_crate.shapes
+import typing
+
+Nothing: typing.TypeAlias = None
+Ellipses: typing.TypeAlias = ...
+Int: typing.TypeAlias = int
+Float: typing.TypeAlias = float
+Complex: typing.TypeAlias = complex
+Bool: typing.TypeAlias = bool
+Str: typing.TypeAlias = str
+Bytes: typing.TypeAlias = bytes
+ByteArray: typing.TypeAlias = bytearray
+Tuple: typing.TypeAlias = tuple[int, str]
+List: typing.TypeAlias = list[float]
+Set: typing.TypeAlias = set[bytes]
+Dict: typing.TypeAlias = dict[str, bool]
+Iterator: typing.TypeAlias = typing.Iterator[int]
+Any: typing.TypeAlias = typing.Any
+Optional: typing.TypeAlias = str | None
+Literal: typing.TypeAlias = typing.Literal[-1, \"it's\", True]
+Callable: typing.TypeAlias = typing.Callable[[int, str], None]
+Custom: typing.TypeAlias = Nothing
+ForeignCustom: typing.TypeAlias = other_crate.shapes.Circle
+Class: typing.TypeAlias = other_crate.shapes.Polygon[int]
+MovedClass: typing.TypeAlias = geometry.shapes.Point2D
+Union: typing.TypeAlias = int | str | None
+Never: typing.TypeAlias = typing.Never
+T = typing.TypeVar('T')
+TypeVar: typing.TypeAlias = T
+Nested: typing.TypeAlias = typing.Iterator[tuple[int, list[set[str]], dict[tuple[bool, str], dict[str, typing.Iterator[float]]]]]
+FilePath: typing.TypeAlias = str | os.PathLike[str]
+Timeout: typing.TypeAlias = datetime.timedelta
+"
+    );
+}
+
+#[test]
+fn optional_style() {
+    let stubs = pyly(ROUND_TRIP, "round_trip.pyi", &["--optional", "typing"]);
+
+    assert!(stubs.contains("Optional: typing.TypeAlias = typing.Optional[str]\n"));
+}
+
+/// Items can be renamed, or moved into another module,
+/// along with their fields.
+#[test]
+fn options() {
+    let stubs = pyly(TEST_CRATE, "geometry/shapes.pyi", &[]);
+
+    assert_eq!(
+        stubs,
+        "\
+class Point2D:
+    \"\"\"Where a :class:`test_crate.Shape` is: see :func:`test_crate.centre`.\"\"\"
+    x: float
+    \"\"\"How far right of the origin it is.\"\"\"
+    y: float
+"
+    );
+}
+
+/// Each Rust module gets its own stubs, in its crate's package,
+/// importing the others it refers to.
+#[test]
+fn packages() {
+    let stubs = pyly(TEST_CRATE, "test_crate/colors.pyi", &[]);
+
+    assert_eq!(
+        stubs,
+        "\
+import enum
+import test_crate
+
+class Color(enum.Enum):
+    \"\"\"A primary colour.\"\"\"
+    Red = 0
+    Green = 1
+    Blue = 2
+
+def fill(shape: test_crate.Shape) -> Color:
+    \"\"\"The colour to fill a :class:`test_crate.Shape` with.\"\"\"
+"
+    );
+}
+
+#[test]
+fn result_style() {
+    let stubs = pyly(
+        TEST_CRATE,
+        "test_crate/__init__.pyi",
+        &["--result", "union"],
+    );
+
+    assert!(stubs.contains(
+        "    def get(self, index: int) -> T | builtins.IndexError:
+        \"\"\"The item at `index`, unless that's past the end (an :class:`OutOfBounds`).\"\"\"
+"
+    ));
+}
+
+#[test]
+fn typescript() {
+    let stubs = pyly(
+        TEST_CRATE,
+        "test_crate/index.d.ts",
+        &["--lang", "typescript"],
+    );
+
+    assert_eq!(
+        stubs,
+        "\
+import type * as geometry_shapes from \"../geometry/shapes\";
+export { Color } from \"../test_crate/colors\";
+export { fill as paint } from \"../test_crate/colors\";
+
+export interface A {
+    _0: number;
+    _1: number;
+}
+
+export interface Pair {
+    left: A;
+    right: A;
+    label: string;
+    seen: Set<number>;
+    history: Array<A>;
+    nickname: string | null;
+    describe(): string;
+    rename(nickname: string | null): void;
+}
+
+export declare const Pair: {
+    \"new\"(left: A, right: A, label: string): Pair;
+};
+
+/** A point of the compass. */
+export enum Direction {
+    /** Up, on a map. */
+    North,
+    East,
+    South,
+    West,
+}
+
+export enum Level {
+    Low = -1,
+    High = 10,
+}
+
+export interface _Shape {
+    area(): number;
+}
+
+/** Every point {@link ShapeCircle.radius | `radius`} away from the origin. */
+export interface ShapeCircle extends _Shape {
+    radius: number;
+}
+
+export interface ShapeLine extends _Shape {
+    _0: Direction;
+    _1: number;
+}
+
+export interface ShapePoint extends _Shape {}
+
+export type Shape = ShapeCircle | ShapeLine | ShapePoint;
+
+/**
+ * A page of items.
+ *
+ * Get one with {@link Page.get}, or make a page with {@link page}.
+ */
+export interface Page<T> {
+    items: Array<T>;
+    number: number;
+    last(): T | null;
+    /**
+     * The item at `index`, unless that's past the end (an {@link OutOfBounds}).
+     *
+     * @throws {RangeError}
+     */
+    get(index: number): T;
+    zip<V>(other: Page<V>): Page<[T, V]>;
+}
+
+/** Visits each item of a {@link Page}, until told to stop. */
+export interface Visitor<T> {
+    /** Whether to carry on. */
+    visit(item: T): boolean;
+}
+
+export declare const Visitor: {
+    fresh<T>(): Visitor<T>;
+};
+
+export interface Limited {
+    LIMIT: number;
+}
+
+export function distance(shape: Shape, _1: [number, number]): number;
+export function centre(shape: Shape): geometry_shapes.Point2D;
+export function page(items: Array<string>, number: number): Page<string>;
+export function walk(page: Page<string>, visitor: Visitor<string>): number;
+/** The most items a {@link Page} holds. */
+export declare const PAGE_SIZE: 50;
+export declare const GREETING: \"Hello, \\\"world\\\"!\";
+export declare const STRICT: true;
+export declare const OFFSET: -3;
+export declare const ORIGIN: [number, number];
+export type OutOfBounds = RangeError;
+export type Stack<T> = Array<T>;
+export type Svelte = [null, number, number, boolean, string, [Array<number>, Set<number>, Map<string, number>]];
+"
+    );
+}
+
+/// Every [pyly_lib::typescript::Type] should read back as the same type.
+#[test]
+fn round_trip_typescript() {
+    let stubs = pyly(
+        ROUND_TRIP,
+        "round_trip/typescript.d.ts",
+        &["--lang", "typescript"],
+    );
+
+    assert_eq!(
+        stubs,
+        "\
+import type * as other_crate_shapes from \"../other_crate/shapes\";
+
+export type TsNull = null;
+export type TsBoolean = boolean;
+export type TsNumber = number;
+export type TsBigInt = bigint;
+export type TsString = string;
+export type TsUnknown = unknown;
+export type TsArray = Array<number>;
+export type TsTuple = [number, string];
+export type TsRecord = Record<string, boolean>;
+export type TsSet = Set<bigint>;
+export type TsMap = Map<number, unknown>;
+export type TsIterator = Iterator<boolean>;
+export type TsUnion = string | null;
+export type TsParameter<T> = T;
+export type TsInterface = TsNull;
+export type TsForeignInterface = other_crate_shapes.Circle;
+export type TsNested = Iterator<[number, Array<Set<string>>, Map<[boolean, string], Map<bigint, number | null>>]>;
+export type TsFilePath = string;
+export type TsTimes = [number, Date];
+"
+    );
+}
+
+/// Failing to evaluate an `AS` is a compiler error, pointing at the constant.
+#[test]
+fn bad_const() {
+    let (output, _) = run(BAD_CONST, "bad_const.pyi", &[]);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+
+    assert!(!output.status.success());
+    assert!(
+        stderr.contains(
+            "error: could not evaluate `<Unfinished as pyly_lib::Exposed<pyly_lib::Python>>::AS`"
+        ),
+        "{stderr}"
+    );
+    assert!(stderr.contains("src/lib.rs:11:5"), "{stderr}");
+    assert!(!stderr.contains("internal compiler error"), "{stderr}");
+}
+
+/// Stubs that can't be written are errors, naming the file.
+#[test]
+fn write_error() {
+    // A file where the `geometry` package's directory should be.
+    let out_dir = Path::new(env!("CARGO_TARGET_TMPDIR"))
+        .join("write_error")
+        .join(std::process::id().to_string());
+    fs::create_dir_all(&out_dir).expect("create the output directory");
+    fs::write(out_dir.join("geometry"), "").expect("write over the package");
+
+    let (output, _) = run(TEST_CRATE, "write_error", &[]);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+
+    assert!(!output.status.success());
+    let path = out_dir.join("geometry/shapes.pyi");
+    assert!(
+        stderr.contains(&format!(
+            "error: could not write stubs to `{}`",
+            path.display()
+        )),
+        "{stderr}"
+    );
+}
+
+/// Unknown options are errors, pointing at the option.
+#[test]
+fn bad_option() {
+    let (output, _) = run(BAD_OPTION, "bad_option.pyi", &[]);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+
+    assert!(!output.status.success());
+    assert!(
+        stderr.contains("error: unknown option `nmae`, expected `name`, `module` or `crate`"),
+        "{stderr}"
+    );
+    assert!(stderr.contains("src/lib.rs:9:16"), "{stderr}");
+    assert!(
+        stderr.contains("error: unknown option `skp`, expected `skip` or `rename`"),
+        "{stderr}"
+    );
+    assert!(stderr.contains("src/lib.rs:11:12"), "{stderr}");
+}
+
+/// Only crates which expose something get stubs.
+#[test]
+fn workspace() {
+    let (output, out_dir) = run(WORKSPACE, "workspace", &[]);
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let mut stubs = fs::read_dir(out_dir)
+        .expect("read the output directory")
+        .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+        .collect::<Vec<_>>();
+    stubs.sort();
+
+    assert_eq!(stubs, ["palette.pyi", "shapes.pyi"]);
+}
+
+#[test]
+fn package() {
+    let (output, out_dir) = run(WORKSPACE, "package", &["--package", "shapes"]);
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    assert!(out_dir.join("shapes.pyi").exists());
+    assert!(!out_dir.join("palette.pyi").exists());
+}
+
+/// The impls `#[pyly::expose]` generates find `pyly_lib` by its own name too,
+/// when it isn't renamed to `pyly`.
+#[test]
+fn crate_name() {
+    let (output, out_dir) = run(WORKSPACE, "crate_name", &["--package", "shapes"]);
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let stubs = fs::read_to_string(out_dir.join("shapes.pyi")).expect("read the stub file");
+    assert_eq!(
+        stubs,
+        "\
+import typing
+
+class Circle:
+    radius: float
+
+Circles: typing.TypeAlias = list[Circle]
+"
+    );
+}
+
+/// PyO3's own classes and functions, as they are at runtime.
+#[test]
+fn pyo3() {
+    let stubs = pyly(PYO3, "bookshop.pyi", &[]);
+
+    assert_eq!(
+        stubs,
+        "\
+class Volume:
+    \"\"\"A book, for sale.\"\"\"
+    title: str
+    \"\"\"What it's called.\"\"\"
+    @property
+    def pages(self) -> int: ...
+    isbn: str | None
+    \"\"\"Its ISBN, if it has one.\"\"\"
+    @property
+    def cost(self) -> float: ...
+    shelf: int
+    def __init__(self, title: str, page_count: int) -> None: ...
+    def remaining(self, page: int) -> int:
+        \"\"\"How many pages are left, from `page`.\"\"\"
+    def twin(self) -> Volume: ...
+    @staticmethod
+    def blank() -> Volume: ...
+    @staticmethod
+    def titled(title: str) -> Volume: ...
+    def __repr__(self) -> str: ...
+
+def total_pages(books: list[int]) -> int:
+    \"\"\"The total of every book's pages.\"\"\"
+def shout(text: str) -> str: ...
+"
+    );
+}
+
+/// What PyO3's macros expand to is theirs to change in any release,
+/// so `tests/pyo3` has to be built with the one the plugin reads.
+#[test]
+fn pyo3_version() {
+    let output = Command::new(env!("CARGO"))
+        .args(["pkgid", "pyo3"])
+        .current_dir(PYO3)
+        .output()
+        .expect("run cargo pkgid");
+    let pkgid = String::from_utf8_lossy(&output.stdout);
+
+    assert!(pkgid.contains("#pyo3@0.23."), "{pkgid}");
+}
+
+/// What PyO3 marks, but not the way the plugin reads, is pointed out.
+#[test]
+fn pyo3_unread() {
+    let (output, _) = run(PYO3, "pyo3_unread", &[]);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+
+    assert!(output.status.success(), "{stderr}");
+    assert!(
+        stderr.contains(
+            "warning: `unreadable` is exposed with PyO3, but not as PyO3 0.23 does: \
+             it's left out of the stubs"
+        ),
+        "{stderr}"
+    );
+}
+
+#[test]
+fn serde() {
+    let stubs = pyly(SERDE, "wire.pyi", &["--serde"]);
+
+    assert_eq!(
+        stubs,
+        "\
+import typing
+
+class Author(typing.TypedDict):
+    \"\"\"Who wrote something.\"\"\"
+    firstName: str
+    surname: str
+    born: typing.NotRequired[int | None]
+    \"\"\"When they were born, if we know.\"\"\"
+    email: str
+
+Ticket = typing.TypedDict('Ticket', {'seat-number': int})
+
+class Ping(typing.TypedDict):
+    kind: typing.Literal['Ping']
+    at: int
+
+Isbn: typing.TypeAlias = str
+Pages: typing.TypeAlias = int
+Format: typing.TypeAlias = typing.Literal['HARDBACK', 'PAPER_BACK', 'ebook']
+
+class EventSold(typing.TypedDict):
+    \"\"\"Someone bought a copy.\"\"\"
+    price: float
+    buyer: Author
+
+Event: typing.TypeAlias = typing.Literal['opened'] | dict[typing.Literal['moved'], tuple[int, int]] | dict[typing.Literal['renamed'], str] | dict[typing.Literal['sold'], EventSold]
+\"\"\"Something that happened.\"\"\"
+
+class ShapeCircle(typing.TypedDict):
+    type: typing.Literal['Circle']
+    radiusMm: float
+
+ShapeWritten = typing.TypedDict('ShapeWritten', {'type': typing.Literal['Written'], 'seat-number': int})
+
+class ShapeNothing(typing.TypedDict):
+    type: typing.Literal['Nothing']
+
+Shape: typing.TypeAlias = ShapeCircle | ShapeWritten | ShapeNothing
+T = typing.TypeVar('T')
+
+class MessageText(typing.TypedDict, typing.Generic[T]):
+    t: typing.Literal['Text']
+    c: str
+
+class MessageDataContent(typing.TypedDict, typing.Generic[T]):
+    body: T
+
+class MessageData(typing.TypedDict, typing.Generic[T]):
+    t: typing.Literal['Data']
+    c: MessageDataContent[T]
+
+class MessageEmpty(typing.TypedDict, typing.Generic[T]):
+    t: typing.Literal['Empty']
+
+Message: typing.TypeAlias = MessageText[T] | MessageData[T] | MessageEmpty[T]
+
+class ReferenceTitle(typing.TypedDict):
+    title: str
+
+Reference: typing.TypeAlias = Isbn | ReferenceTitle | None
+
+class Shelf:
+    \"\"\"Not serialized, so still a class.\"\"\"
+    books: list[Isbn]
+
+def latest(_shelf: Shelf) -> Event | None: ...
+"
+    );
+}
+
+#[test]
+fn serde_typescript() {
+    let stubs = pyly(SERDE, "wire.d.ts", &["--serde", "--lang", "typescript"]);
+
+    assert_eq!(
+        stubs,
+        "\
+/** Who wrote something. */
+export interface Author {
+    firstName: string;
+    surname: string;
+    /** When they were born, if we know. */
+    born?: number | null;
+    email: string;
+}
+
+export interface Ticket {
+    \"seat-number\": number;
+}
+
+export interface Ping {
+    kind: \"Ping\";
+    at: number;
+}
+
+export type Isbn = string;
+export type Pages = number;
+export type Format = \"HARDBACK\" | \"PAPER_BACK\" | \"ebook\";
+
+/** Someone bought a copy. */
+export interface EventSold {
+    price: number;
+    buyer: Author;
+}
+
+/** Something that happened. */
+export type Event = \"opened\" | { moved: [number, number] } | { renamed: string } | { sold: EventSold };
+
+export interface ShapeCircle {
+    type: \"Circle\";
+    radiusMm: number;
+}
+
+export interface ShapeWritten {
+    type: \"Written\";
+    \"seat-number\": number;
+}
+
+export interface ShapeNothing {
+    type: \"Nothing\";
+}
+
+export type Shape = ShapeCircle | ShapeWritten | ShapeNothing;
+
+export interface MessageText<T> {
+    t: \"Text\";
+    c: string;
+}
+
+export interface MessageDataContent<T> {
+    body: T;
+}
+
+export interface MessageData<T> {
+    t: \"Data\";
+    c: MessageDataContent<T>;
+}
+
+export interface MessageEmpty<T> {
+    t: \"Empty\";
+}
+
+export type Message<T> = MessageText<T> | MessageData<T> | MessageEmpty<T>;
+
+export interface ReferenceTitle {
+    title: string;
+}
+
+export type Reference = Isbn | ReferenceTitle | null;
+
+/** Not serialized, so still a class. */
+export interface Shelf {
+    books: Array<Isbn>;
+}
+
+export function latest(_shelf: Shelf): Event | null;
+"
+    );
+}
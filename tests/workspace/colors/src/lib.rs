@@ -0,0 +1,7 @@
+use pyly::{Exposed, Python as Py};
+
+pub struct Rgb;
+
+impl Exposed<Py> for Rgb {
+    const AS: <Py as pyly::Language>::Type = <(u8, u8, u8) as Exposed<Py>>::AS;
+}
@@ -0,0 +1,3 @@
+//! Uses PyLy, but exposes nothing.
+
+pub use pyly::Exposed;
@@ -0,0 +1,3 @@
+//! Doesn't use PyLy at all.
+
+pub struct Plain;
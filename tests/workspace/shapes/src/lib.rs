@@ -0,0 +1,16 @@
+//! Depends on `pyly_lib` by its own name, rather than as `pyly`.
+#![feature(register_tool)]
+#![register_tool(__pyly)]
+
+use pyly_lib::{Exposed, Python as Py};
+
+#[pyly_lib::expose]
+pub struct Circle {
+    pub radius: f64,
+}
+
+pub struct Circles;
+
+impl Exposed<Py> for Circles {
+    const AS: <Py as pyly_lib::Language>::Type = <Vec<Circle> as Exposed<Py>>::AS;
+}